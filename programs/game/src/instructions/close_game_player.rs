@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use matchmaking::Game;
+use crate::state::GamePlayer;
+
+#[error_code]
+pub enum CloseGamePlayerError {
+    #[msg("Match has not ended yet")]
+    MatchNotEnded,
+    #[msg("Final stats have not been reported for settlement yet")]
+    ResultNotReported,
+}
+
+/// Close a GamePlayer once its match is over and refund the rent to the authority
+/// Call on the base layer after undelegate_game_player - a delegated account is
+/// not owned by this program and is rejected
+pub fn handler(ctx: Context<CloseGamePlayer>) -> Result<()> {
+    let game_player = &ctx.accounts.game_player;
+    let game_info = ctx.accounts.game.to_account_info();
+
+    // The Game may already have been closed as stale, nothing left to settle then
+    let game_closed = game_info.owner != &matchmaking::ID || game_info.data_is_empty();
+
+    if !game_closed {
+        let game = Game::try_deserialize(&mut &game_info.try_borrow_data()?[..])?;
+        require!(game.game_state == 2, CloseGamePlayerError::MatchNotEnded);
+
+        // Before settlement, players must report first so their result counts
+        if !game.is_settled {
+            require!(
+                game_player.is_settled || game_player.is_spectator || game_player.is_idle,
+                CloseGamePlayerError::ResultNotReported
+            );
+        }
+    }
+
+    msg!("Closed GamePlayer {} for game {}", game_player.key(), game_info.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseGamePlayer<'info> {
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [
+            b"game_player",
+            authority.key().as_ref(),
+            game.key().as_ref()
+        ],
+        bump = game_player.bump
+    )]
+    pub game_player: Account<'info, GamePlayer>,
+
+    /// CHECK: Matchmaking Game the GamePlayer belongs to, may already be closed -
+    /// only deserialized while still owned by matchmaking
+    pub game: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
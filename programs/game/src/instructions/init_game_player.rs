@@ -3,6 +3,7 @@ use crate::state::GamePlayer;
 
 /// Initialize a game player account when a player joins a game
 /// This creates the game-specific player state separate from matchmaking
+/// An existing account for the same game (a reconnect) keeps its stats
 pub fn handler(
     ctx: Context<InitGamePlayer>,
     game_id: Pubkey,
@@ -15,6 +16,31 @@ pub fn handler(
     let game_player = &mut ctx.accounts.game_player;
     let clock = Clock::get()?;
 
+    // Reconnecting to a match in progress - keep stats, and position and health unless kicked
+    if game_player.authority == ctx.accounts.authority.key() && game_player.game_id == game_id {
+        // Kicked for inactivity is not a death - back to the spawn point at full health
+        if game_player.is_idle {
+            game_player.is_idle = false;
+            game_player.position_x = spawn_x;
+            game_player.position_y = spawn_y;
+            game_player.position_z = spawn_z;
+            game_player.health = 100;
+            game_player.is_alive = true;
+            game_player.death_timestamp = 0;
+        }
+        // The idle timer starts over from the reconnect
+        game_player.last_update = clock.unix_timestamp;
+
+        msg!(
+            "Reusing game player {} for game {} (kills: {}, deaths: {})",
+            ctx.accounts.authority.key(),
+            game_id,
+            game_player.kills,
+            game_player.deaths
+        );
+        return Ok(());
+    }
+
     game_player.authority = ctx.accounts.authority.key();
     game_player.game_id = game_id;
 
@@ -47,6 +73,8 @@ pub fn handler(
     game_player.reload_start_timestamp = 0; // Not reloading
 
     game_player.bump = ctx.bumps.game_player;
+    game_player.is_settled = false;
+    game_player.is_idle = false;
 
     msg!(
         "Initialized game player {} for game {} at position ({}, {}, {})",
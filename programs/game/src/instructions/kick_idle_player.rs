@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::anchor::commit;
+use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
+use matchmaking::cpi::accounts::RemoveIdlePlayer;
+use matchmaking::program::Matchmaking;
+use matchmaking::{Game, Player, SETTLEMENT_AUTHORITY_SEED};
+use crate::state::GamePlayer;
+use crate::PLAYER_SEED;
+
+#[error_code]
+pub enum KickIdleError {
+    #[msg("Player has sent input within the idle timeout")]
+    PlayerNotIdle,
+    #[msg("Player has already been kicked")]
+    AlreadyIdle,
+    #[msg("Player has not been flagged idle")]
+    NotFlaggedIdle,
+    #[msg("Spectators cannot be kicked")]
+    SpectatorCannotBeKicked,
+}
+
+/// Flag a player idle once their last input is older than the match's idle timeout
+/// Permissionless, runs on the game ephemeral rollup - the GamePlayer is committed
+/// and undelegated so remove_idle_player can take them off their team on the base layer
+pub fn kick_handler(ctx: Context<KickIdlePlayer>) -> Result<()> {
+    let game_player = &mut ctx.accounts.game_player;
+    let clock = Clock::get()?;
+
+    require!(!game_player.is_spectator, KickIdleError::SpectatorCannotBeKicked);
+    require!(!game_player.is_idle, KickIdleError::AlreadyIdle);
+    require!(
+        ctx.accounts.game.is_player_idle(game_player.last_update, clock.unix_timestamp),
+        KickIdleError::PlayerNotIdle
+    );
+
+    game_player.is_idle = true;
+    game_player.is_alive = false;
+    game_player.health = 0;
+
+    msg!(
+        "GamePlayer {} idle for {}s - kicking",
+        game_player.key(),
+        clock.unix_timestamp - game_player.last_update
+    );
+
+    game_player.exit(&crate::ID)?;
+    commit_and_undelegate_accounts(
+        &ctx.accounts.payer,
+        vec![&ctx.accounts.game_player.to_account_info()],
+        &ctx.accounts.magic_context,
+        &ctx.accounts.magic_program,
+    )?;
+
+    Ok(())
+}
+
+/// Remove a kicked player from their matchmaking team
+/// Call on the base layer after kick_idle_player has undelegated the GamePlayer
+pub fn remove_handler(ctx: Context<RemoveIdleGamePlayer>) -> Result<()> {
+    require!(ctx.accounts.game_player.is_idle, KickIdleError::NotFlaggedIdle);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        SETTLEMENT_AUTHORITY_SEED.as_bytes(),
+        &[ctx.bumps.settlement_authority],
+    ]];
+
+    matchmaking::cpi::remove_idle_player(CpiContext::new_with_signer(
+        ctx.accounts.matchmaking_program.to_account_info(),
+        RemoveIdlePlayer {
+            game: ctx.accounts.game.to_account_info(),
+            player: ctx.accounts.player.to_account_info(),
+            settlement_authority: ctx.accounts.settlement_authority.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    msg!("GamePlayer {} removed from game {}", ctx.accounts.game_player.key(), ctx.accounts.game.key());
+
+    Ok(())
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct KickIdlePlayer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"game_player",
+            game_player.authority.as_ref(),
+            game.key().as_ref()
+        ],
+        bump = game_player.bump
+    )]
+    pub game_player: Account<'info, GamePlayer>,
+
+    /// Matchmaking Game the player is in (read for the idle timeout)
+    pub game: Account<'info, Game>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveIdleGamePlayer<'info> {
+    #[account(
+        seeds = [
+            b"game_player",
+            game_player.authority.as_ref(),
+            game.key().as_ref()
+        ],
+        bump = game_player.bump
+    )]
+    pub game_player: Account<'info, GamePlayer>,
+
+    /// Matchmaking Game the GamePlayer belongs to
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    /// Matchmaking Player of the GamePlayer's authority
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED, game_player.authority.as_ref()],
+        bump,
+        seeds::program = matchmaking::ID
+    )]
+    pub player: Account<'info, Player>,
+
+    /// CHECK: PDA signer for the matchmaking CPI, holds no data
+    #[account(
+        seeds = [SETTLEMENT_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub matchmaking_program: Program<'info, Matchmaking>,
+}
@@ -2,14 +2,18 @@ pub mod init_game_player;
 pub mod delegate_game_player;
 pub mod undelegate_game_player;
 pub mod shoot;
-pub mod award_kill;
 pub mod respawn_player;
 pub mod reload;
+pub mod settle_game_player;
+pub mod kick_idle_player;
+pub mod close_game_player;
 
 pub use init_game_player::*;
 pub use delegate_game_player::*;
 pub use undelegate_game_player::*;
 pub use shoot::*;
-pub use award_kill::*;
 pub use respawn_player::*;
 pub use reload::*;
+pub use settle_game_player::*;
+pub use kick_idle_player::*;
+pub use close_game_player::*;
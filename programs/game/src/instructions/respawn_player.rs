@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use matchmaking::Game;
 use crate::state::GamePlayer;
 
 #[error_code]
@@ -7,6 +8,8 @@ pub enum RespawnError {
     PlayerStillAlive,
     #[msg("Respawn cooldown not finished. Wait 3 seconds after death.")]
     RespawnCooldownActive,
+    #[msg("Match is paused")]
+    MatchPaused,
 }
 
 /// Respawn a dead player at a spawn point
@@ -20,6 +23,9 @@ pub fn handler(
     let player = &mut ctx.accounts.game_player;
     let clock = Clock::get()?;
 
+    // No input while the match is paused
+    require!(!ctx.accounts.game.is_paused(), RespawnError::MatchPaused);
+
     // Check if player is dead
     require!(!player.is_alive, RespawnError::PlayerStillAlive);
 
@@ -69,5 +75,9 @@ pub struct RespawnPlayer<'info> {
     #[account(mut)]
     pub game_player: Account<'info, GamePlayer>,
 
+    /// Matchmaking Game the player is in (read for pause state)
+    #[account(address = game_player.game_id)]
+    pub game: Account<'info, Game>,
+
     pub authority: Signer<'info>,
 }
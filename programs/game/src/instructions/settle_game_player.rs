@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use matchmaking::cpi::accounts::RecordPlayerResult;
+use matchmaking::program::Matchmaking;
+use matchmaking::{
+    Game, LevelCurve, Player, LEVEL_CURVE_SEED, MATCH_HISTORY_SEED, MATCH_RECORD_SEED,
+    SETTLEMENT_AUTHORITY_SEED,
+};
+use crate::state::GamePlayer;
+use crate::PLAYER_SEED;
+
+#[error_code]
+pub enum SettleError {
+    #[msg("GamePlayer stats have already been reported")]
+    AlreadySettled,
+
+    #[msg("Spectators have no results to report")]
+    SpectatorCannotSettle,
+}
+
+/// Report a player's final stats to matchmaking for settlement
+/// Call on the base layer after the GamePlayer has been undelegated and the match has ended
+/// Permissionless - the stats come straight from the committed GamePlayer account
+/// The GamePlayer is closed afterwards and its rent refunded to the authority
+pub fn handler(ctx: Context<SettleGamePlayer>) -> Result<()> {
+    let game_player = &mut ctx.accounts.game_player;
+
+    require!(!game_player.is_settled, SettleError::AlreadySettled);
+    require!(!game_player.is_spectator, SettleError::SpectatorCannotSettle);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        SETTLEMENT_AUTHORITY_SEED.as_bytes(),
+        &[ctx.bumps.settlement_authority],
+    ]];
+
+    matchmaking::cpi::record_player_result(
+        CpiContext::new_with_signer(
+            ctx.accounts.matchmaking_program.to_account_info(),
+            RecordPlayerResult {
+                game: ctx.accounts.game.to_account_info(),
+                player: ctx.accounts.player.to_account_info(),
+                level_curve: ctx.accounts.level_curve.to_account_info(),
+                match_record: ctx.accounts.match_record.to_account_info(),
+                match_history: ctx.accounts.match_history.to_account_info(),
+                settlement_authority: ctx.accounts.settlement_authority.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        game_player.kills,
+        game_player.deaths,
+        game_player.score,
+    )?;
+
+    game_player.is_settled = true;
+
+    msg!(
+        "GamePlayer {} settled - Kills: {}, Deaths: {}, Score: {}",
+        game_player.key(),
+        game_player.kills,
+        game_player.deaths,
+        game_player.score
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleGamePlayer<'info> {
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [
+            b"game_player",
+            game_player.authority.as_ref(),
+            game.key().as_ref()
+        ],
+        bump = game_player.bump
+    )]
+    pub game_player: Account<'info, GamePlayer>,
+
+    /// Wallet of the GamePlayer, receives the rent when it is closed
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    /// Matchmaking Game the GamePlayer belongs to
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    /// Matchmaking Player of the GamePlayer's authority
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED, game_player.authority.as_ref()],
+        bump,
+        seeds::program = matchmaking::ID
+    )]
+    pub player: Account<'info, Player>,
+
+    /// Matchmaking level curve used to award XP
+    #[account(
+        seeds = [LEVEL_CURVE_SEED.as_bytes()],
+        bump,
+        seeds::program = matchmaking::ID
+    )]
+    pub level_curve: Account<'info, LevelCurve>,
+
+    /// CHECK: Matchmaking MatchRecord PDA, created and validated by matchmaking
+    #[account(
+        mut,
+        seeds = [MATCH_RECORD_SEED.as_bytes(), game.key().as_ref()],
+        bump,
+        seeds::program = matchmaking::ID
+    )]
+    pub match_record: UncheckedAccount<'info>,
+
+    /// CHECK: Matchmaking PlayerMatchHistory PDA, created and validated by matchmaking
+    #[account(
+        mut,
+        seeds = [MATCH_HISTORY_SEED.as_bytes(), game_player.authority.as_ref()],
+        bump,
+        seeds::program = matchmaking::ID
+    )]
+    pub match_history: UncheckedAccount<'info>,
+
+    /// CHECK: PDA signer for the matchmaking CPI, holds no data
+    #[account(
+        seeds = [SETTLEMENT_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub settlement_authority: UncheckedAccount<'info>,
+
+    pub matchmaking_program: Program<'info, Matchmaking>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
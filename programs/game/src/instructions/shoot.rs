@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
+use matchmaking::Game;
 use crate::state::GamePlayer;
+use crate::KILL_SCORE;
 
 #[error_code]
 pub enum ShootError {
@@ -8,15 +10,21 @@ pub enum ShootError {
     
     #[msg("Cannot shoot while dead. Wait for respawn.")]
     ShooterDead,
+
+    #[msg("Match is paused.")]
+    MatchPaused,
 }
 
 /// Shoot and check if any player is hit
 /// Uses ray-box intersection to detect hits
-/// Automatically awards kill and score if target is killed
-pub fn handler(ctx: Context<Shoot>, damage: u8, kill_score: u32) -> Result<()> {
+/// Automatically awards the kill and KILL_SCORE if the target is killed
+pub fn handler(ctx: Context<Shoot>, damage: u8) -> Result<()> {
     let shooter = &mut ctx.accounts.shooter;
     let clock = Clock::get()?;
 
+    // No input while the match is paused
+    require!(!ctx.accounts.game.is_paused(), ShootError::MatchPaused);
+
     // Check if shooter is alive
     require!(shooter.is_alive, ShootError::ShooterDead);
 
@@ -123,7 +131,7 @@ pub fn handler(ctx: Context<Shoot>, damage: u8, kill_score: u32) -> Result<()> {
 
             // Award kill and score to shooter
             shooter.kills = shooter.kills.saturating_add(1);
-            shooter.score = shooter.score.saturating_add(kill_score);
+            shooter.score = shooter.score.saturating_add(KILL_SCORE);
             shooter.last_update = clock.unix_timestamp;
 
             msg!(
@@ -210,9 +218,13 @@ fn ray_box_intersection(
 #[derive(Accounts)]
 pub struct Shoot<'info> {
     /// The player shooting (mutable to update kill stats)
-    #[account(mut)]
+    #[account(mut, has_one = authority)]
     pub shooter: Account<'info, GamePlayer>,
 
+    /// Matchmaking Game the shooter is playing in (read for pause state)
+    #[account(address = shooter.game_id)]
+    pub game: Account<'info, Game>,
+
     pub authority: Signer<'info>,
 
     // Remaining accounts: other GamePlayer accounts to check for hits
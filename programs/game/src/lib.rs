@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::anchor::{commit, ephemeral};
 use ephemeral_rollups_sdk::ephem::commit_accounts;
+use matchmaking::Game;
 
 declare_id!("BxRTWoDrsLCguuGVMgAEVJgfRPpmdiGzjFvXYhTUmMLU");
 
 pub const PLAYER_SEED: &[u8] = b"player";
+/// Score awarded for every kill - fixed so settlement can trust team scores
+pub const KILL_SCORE: u32 = 100;
 
 mod instructions;
 mod state;
@@ -51,6 +54,9 @@ pub mod game {
         let player = &mut ctx.accounts.game_player;
         let clock = Clock::get()?;
 
+        // No input while the match is paused
+        require!(!ctx.accounts.game.is_paused(), InputError::MatchPaused);
+
         // Store rotation values directly from frontend
         // Frontend calculates rotation, contract just stores it
         player.rotation_x = rotation_x;
@@ -173,14 +179,9 @@ pub mod game {
 
     /// Shoot and check for hits on other players
     /// Pass other player accounts in remaining_accounts to check for hits
-    /// Automatically awards kill and score if target is killed
-    pub fn shoot(ctx: Context<Shoot>, damage: u8, kill_score: u32) -> Result<()> {
-        shoot::handler(ctx, damage, kill_score)
-    }
-
-    /// Award a kill to the shooter (called after successful kill)
-    pub fn award_kill(ctx: Context<AwardKill>, score_points: u32) -> Result<()> {
-        award_kill::handler(ctx, score_points)
+    /// Automatically awards the kill and KILL_SCORE if the target is killed
+    pub fn shoot(ctx: Context<Shoot>, damage: u8) -> Result<()> {
+        shoot::handler(ctx, damage)
     }
 
     /// Respawn a dead player at spawn point
@@ -204,6 +205,27 @@ pub mod game {
     pub fn reload(ctx: Context<Reload>) -> Result<()> {
         reload::reload_handler(ctx)
     }
+
+    /// Report final stats to matchmaking once the match has ended
+    /// Call on the base layer after undelegate_game_player - closes the GamePlayer
+    pub fn settle_game_player(ctx: Context<SettleGamePlayer>) -> Result<()> {
+        settle_game_player::handler(ctx)
+    }
+
+    /// Flag an AFK player idle and undelegate their GamePlayer (ephemeral rollup)
+    pub fn kick_idle_player(ctx: Context<KickIdlePlayer>) -> Result<()> {
+        kick_idle_player::kick_handler(ctx)
+    }
+
+    /// Take a kicked player off their matchmaking team (base layer)
+    pub fn remove_idle_player(ctx: Context<RemoveIdleGamePlayer>) -> Result<()> {
+        kick_idle_player::remove_handler(ctx)
+    }
+
+    /// Close a GamePlayer after its match has ended, refunding the rent
+    pub fn close_game_player(ctx: Context<CloseGamePlayer>) -> Result<()> {
+        close_game_player::handler(ctx)
+    }
 }
 
 #[derive(Accounts)]
@@ -230,9 +252,19 @@ pub struct ProcessPlayerInput<'info> {
     )]
     pub game_player: Account<'info, GamePlayer>,
 
+    /// Matchmaking Game the player is in (read for pause state)
+    #[account(address = _game_id)]
+    pub game: Account<'info, Game>,
+
     pub authority: Signer<'info>,
 }
 
+#[error_code]
+pub enum InputError {
+    #[msg("Match is paused")]
+    MatchPaused,
+}
+
 #[commit]
 #[derive(Accounts)]
 pub struct CommitPlayerState<'info> {
@@ -53,6 +53,12 @@ pub struct GamePlayer {
 
     /// Bump seed for PDA
     pub bump: u8,
+
+    /// Final stats have been reported to matchmaking for settlement
+    pub is_settled: bool,
+
+    /// Kicked for inactivity, waiting to be removed from the matchmaking team
+    pub is_idle: bool,
 }
 
 impl GamePlayer {
@@ -70,5 +76,7 @@ impl GamePlayer {
         8 + // death_timestamp
         1 + // bullet_count
         8 + // reload_start_timestamp
-        1; // bump
+        1 + // bump
+        1 + // is_settled
+        1; // is_idle
 }
@@ -1,9 +1,22 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::system_program::{self, Transfer};
 
 pub const MAP_REGISTRY_SEED: &[u8] = b"fps.so map-registry";
 pub const MAP_METADATA_SEED: &[u8] = b"fps.so map-metadata";
 pub const MAP_DATA_SEED: &[u8] = b"fps.so map-data";
 pub const USER_MAP_INDEX_SEED: &[u8] = b"fps.so user-map-index";
+pub const MAP_REVISION_SEED: &[u8] = b"fps.so map-revision";
+pub const MAP_RATING_SEED: &[u8] = b"fps.so map-rating";
+pub const MAP_INDEX_SEED: &[u8] = b"fps.so map-index";
+
+/// Matchmaking PDA that reports map plays via CPI
+pub const MAP_PLAY_AUTHORITY_SEED: &[u8] = b"fps.so map-play";
+pub const MATCHMAKING_PROGRAM_ID: Pubkey = pubkey!("FK438BMUMqFqZj33Argueh89XGTwxEPXCEU7JgeMXLvA");
+
+/// Spawn points each team needs before a map can be finalized or published
+pub const MIN_SPAWN_POINTS_PER_TEAM: u16 = 1;
 
 declare_id!("6XPHneawKSf2BWTtfZurtMdVvBiKsriTnGLKjoWdK791");
 
@@ -15,49 +28,108 @@ pub mod map_registry {
     /// 
     /// This should be called once to set up the program's global state.
     /// It creates a PDA account that tracks the total number of default and user-created maps.
+    /// Only the program's upgrade authority can call it, and becomes the registry
+    /// admin who curates the default maps.
     /// 
     /// # Arguments
     /// * `ctx` - The context containing the map_registry account to initialize
     /// 
     /// # Accounts
     /// * `map_registry` - The global registry account (PDA)
-    /// * `user` - The signer paying for account creation
+    /// * `program_data` - This program's ProgramData account, holds the upgrade authority
+    /// * `user` - The upgrade authority, pays for account creation and becomes the admin
     /// * `system_program` - Solana system program for account creation
     pub fn initialize(ctx: Context<InitializeMapRegistry>) -> Result<()> {
         let map_registry = &mut ctx.accounts.map_registry;
-        // Initialize counters to zero
-        if map_registry.default_maps_count != 0 || map_registry.user_maps_count != 0 {
-            msg!("Map registry already initialized. Skipping reset.");
-            return Ok(()); // Exit early if the account already exists
-        }
-
 
+        map_registry.admin = ctx.accounts.user.key();
         map_registry.default_maps_count = 0;
         map_registry.user_maps_count = 0;
+        map_registry.maps_created = 0;
+
+        msg!("Map registry initialized with admin {}", map_registry.admin);
+        Ok(())
+    }
+
+    /// Hands the registry admin role to another wallet
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_registry account
+    /// * `new_admin` - Wallet that becomes the admin
+    /// 
+    /// # Accounts
+    /// * `map_registry` - The global registry account (PDA)
+    /// * `admin` - The current admin
+    pub fn transfer_registry_admin(
+        ctx: Context<TransferRegistryAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        let map_registry = &mut ctx.accounts.map_registry;
+        map_registry.admin = new_admin;
+
+        msg!("Map registry admin transferred to {}", new_admin);
+        Ok(())
+    }
+
+    /// Promotes a map to an official default map, or demotes it back to a user map
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_registry and map_metadata accounts
+    /// * `is_default` - Whether the map should be an official map
+    /// 
+    /// # Accounts
+    /// * `map_registry` - Global registry to update counters
+    /// * `map_metadata` - The map to promote or demote
+    /// * `admin` - The registry admin
+    pub fn set_map_default(ctx: Context<SetMapDefault>, is_default: bool) -> Result<()> {
+        let map_registry = &mut ctx.accounts.map_registry;
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        if map_metadata.is_default == is_default {
+            return Ok(());
+        }
+
+        // Move the map between the two counters
+        if is_default {
+            map_registry.default_maps_count += 1;
+            map_registry.user_maps_count = map_registry.user_maps_count.saturating_sub(1);
+        } else {
+            map_registry.user_maps_count += 1;
+            map_registry.default_maps_count = map_registry.default_maps_count.saturating_sub(1);
+        }
+        map_metadata.is_default = is_default;
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+
         Ok(())
     }
 
     /// Creates a new map with both metadata and data
     /// 
+    /// The map starts out as a draft: more objects can be added with
+    /// append_map_objects/write_map_objects, and it cannot be played until
+    /// finalize_map seals it.
+    /// 
     /// This instruction:
     /// 1. Creates a MapMetadata PDA to store map information (name, description, creator, etc.)
     /// 2. Creates a MapData PDA to store the actual map data (game level data, tiles, etc.)
     /// 3. Updates the global map registry counters
     /// 4. Adds the map to the creator's personal index for easy lookup
+    /// 5. Adds the map to the global map index
     /// 
     /// # Arguments
     /// * `ctx` - The context containing all required accounts
     /// * `map_id` - Unique identifier for the map (used to derive PDAs)
     /// * `name` - Display name of the map
     /// * `description` - Description of the map
-    /// * `is_default` - Whether this is a default/official map or user-created
+    /// * `is_default` - Whether this is a default/official map or user-created (admin only)
     /// * `map_data` - The actual map data as a vector of MapObject (level layout, objects, etc.)
     ///
     /// # Accounts
     /// * `map_metadata` - New PDA to store map metadata
     /// * `map_data_account` - New PDA to store actual map data
-    /// * `map_registry` - Global registry to update counters
+    /// * `map_registry` - Global registry to update counters, holds the admin
     /// * `user_map_index` - User's personal index to track their maps
+    /// * `map_index` - A global map index page with room left
     /// * `user` - The map creator and transaction signer
     /// * `system_program` - Solana system program
     pub fn create_map(
@@ -72,6 +144,13 @@ pub mod map_registry {
         let map_registry = &mut ctx.accounts.map_registry;
         let user_index = &mut ctx.accounts.user_map_index;
         let map_data_account = &mut ctx.accounts.map_data_account;
+        let map_index = &mut ctx.accounts.map_index;
+
+        // Only the registry admin can publish official maps
+        require!(
+            !is_default || map_registry.admin == ctx.accounts.user.key(),
+            ErrorCode::NotRegistryAdmin
+        );
         
         // Store all metadata about the map
         map_metadata.map_id = map_id.clone();
@@ -83,12 +162,29 @@ pub mod map_registry {
         map_metadata.created_at = current_timestamp;
         map_metadata.updated_at = current_timestamp;
         map_metadata.is_default = is_default;
+        map_metadata.status = MapStatus::Draft;
+        map_metadata.rating_count = 0;
+        map_metadata.rating_total = 0;
+        map_metadata.like_count = 0;
+        map_metadata.play_count = 0;
+        map_metadata.category = String::new();
+        map_metadata.tags = Vec::new();
+        map_metadata.is_listed = false;
+        map_metadata.is_forkable = true;
+        map_metadata.fork_count = 0;
+        map_metadata.parent_map_id = String::new();
+        map_metadata.collaborators = Vec::new();
+        map_metadata.pending_owner = Pubkey::default();
+        map_metadata.nonce = map_registry.next_map_nonce();
         
         // Validate map data size and store it
         require!(
-            map_data.len() <= MapData::MAX_SIZE,
+            map_data.len() <= MapData::MAX_OBJECTS,
             ErrorCode::MapDataTooLarge
         );
+        validate_objects(&map_data, 0)?;
+        let bytes = map_data.try_to_vec()?;
+        map_metadata.count_spawn_points(&bytes[4..]);
         map_data_account.objects = map_data;
 
         // Update global counters based on map type
@@ -98,21 +194,248 @@ pub mod map_registry {
             map_registry.user_maps_count += 1;
         }
 
-        // Add map to the user's personal index
-        // This allows users to easily query all maps they've created
-        require!(
-            user_index.map_ids.len() < 100,
-            ErrorCode::UserMapLimitReached
+        // Add map to the user's personal index and the global index
+        index_new_map(map_metadata, user_index, map_index)
+    }
+
+    /// Starts a fork of another creator's map (or your own)
+    /// 
+    /// Creates a new draft map owned by the caller that records the parent map
+    /// and its latest published version. The objects of that revision are then
+    /// copied over with copy_fork_objects, in chunks for large maps; the fork
+    /// cannot be edited or finalized until the copy is complete. Revisions are
+    /// immutable, so the fork always holds exactly the attributed version.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing all required accounts
+    /// * `map_id` - Unique identifier for the new map
+    /// * `name` - Display name of the new map
+    /// * `description` - Description of the new map
+    /// 
+    /// # Accounts
+    /// * `parent_metadata` - The map being forked, its fork counter is bumped
+    /// * `parent_revision` - The parent's latest published revision (read-only)
+    /// * `map_metadata` - New PDA for the fork's metadata
+    /// * `map_data_account` - New PDA for the fork's data, starts empty
+    /// * `map_registry` - Global registry to update counters
+    /// * `user_map_index` - User's personal index to track their maps
+    /// * `map_index` - A global map index page with room left
+    /// * `user` - The fork's creator and transaction signer
+    /// * `system_program` - Solana system program
+    pub fn fork_map(
+        ctx: Context<ForkMap>,
+        map_id: String,
+        name: String,
+        description: String,
+    ) -> Result<()> {
+        let parent_metadata = &mut ctx.accounts.parent_metadata;
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        let parent_revision = &ctx.accounts.parent_revision;
+
+        require!(parent_metadata.is_forkable, ErrorCode::MapNotForkable);
+        require!(parent_metadata.published_version > 0, ErrorCode::NoPublishedRevision);
+
+        let object_count = parent_revision.object_count;
+
+        map_metadata.map_id = map_id;
+        map_metadata.name = name;
+        map_metadata.description = description;
+        map_metadata.creator = ctx.accounts.user.key();
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        map_metadata.created_at = current_timestamp;
+        map_metadata.updated_at = current_timestamp;
+        map_metadata.is_default = false;
+        map_metadata.status = MapStatus::Draft;
+        map_metadata.is_listed = false;
+        map_metadata.is_forkable = true;
+        map_metadata.fork_count = 0;
+        map_metadata.collaborators = Vec::new();
+        map_metadata.pending_owner = Pubkey::default();
+        // The data account starts out empty
+        map_metadata.count_spawn_points(&[]);
+
+        // Attribution
+        map_metadata.parent_map_id = parent_metadata.map_id.clone();
+        map_metadata.parent_version = parent_revision.version;
+        map_metadata.fork_source = parent_revision.key();
+        map_metadata.fork_object_count = object_count;
+        map_metadata.fork_copied_count = 0;
+        parent_metadata.fork_count += 1;
+
+        let map_registry = &mut ctx.accounts.map_registry;
+        map_registry.user_maps_count += 1;
+        map_metadata.nonce = map_registry.next_map_nonce();
+
+        msg!(
+            "Forking map {} version {} into {} ({} objects)",
+            parent_metadata.map_id,
+            parent_revision.version,
+            map_metadata.map_id,
+            object_count
+        );
+        index_new_map(
+            map_metadata,
+            &mut ctx.accounts.user_map_index,
+            &mut ctx.accounts.map_index,
+        )
+    }
+
+    /// Copies the next chunk of the parent revision's objects into a fork
+    /// 
+    /// The source revision is immutable and outlives the parent map, so the copy
+    /// can always be finished.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the parent and fork accounts
+    /// * `max_objects` - Upper bound on objects to copy in this instruction
+    /// 
+    /// # Accounts
+    /// * `parent_revision` - The revision the fork was started from (read-only)
+    /// * `map_metadata` - The fork, takes the revision's spawn counts once the copy is complete
+    /// * `map_data_account` - The fork's data account to fill
+    /// * `user` - The fork's creator or a collaborator, pays the extra rent
+    /// * `system_program` - Needed for the rent top-up
+    pub fn copy_fork_objects(ctx: Context<CopyForkObjects>, max_objects: u32) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        map_metadata.require_editor(&ctx.accounts.user.key())?;
+        require!(map_metadata.is_fork_pending(), ErrorCode::ForkComplete);
+
+        let parent_revision = ctx.accounts.parent_revision.to_account_info();
+        let parent_data = parent_revision.try_borrow_data()?;
+        let object_count = map_metadata.fork_object_count as usize;
+
+        let copied = map_metadata.fork_copied_count as usize;
+        let chunk = (max_objects as usize)
+            .min(object_count - copied)
+            .min(MapRevision::MAX_OBJECTS_PER_COPY);
+        let new_copied = copied + chunk;
+
+        // Size the fork for the objects copied so far
+        let map_data_info = ctx.accounts.map_data_account.to_account_info();
+        resize_map_data(
+            &map_data_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_copied,
+        )?;
+
+        {
+            let source_start = MapRevision::HEADER_SIZE + copied * MapData::OBJECT_SIZE;
+            let target_start = MapData::HEADER_SIZE + copied * MapData::OBJECT_SIZE;
+            let len = chunk * MapData::OBJECT_SIZE;
+            map_data_info.try_borrow_mut_data()?[target_start..target_start + len]
+                .copy_from_slice(&parent_data[source_start..source_start + len]);
+        }
+        map_metadata.fork_copied_count = new_copied as u32;
+
+        // The fork now holds exactly the revision's objects
+        if !map_metadata.is_fork_pending() {
+            map_metadata.blue_spawn_count = ctx.accounts.parent_revision.blue_spawn_count;
+            map_metadata.red_spawn_count = ctx.accounts.parent_revision.red_spawn_count;
+        }
+
+        msg!(
+            "Fork {} - copied {}/{} objects",
+            map_metadata.map_id,
+            new_copied,
+            object_count
         );
-        user_index.map_ids.push(map_id);
-        user_index.map_count += 1;
+        Ok(())
+    }
+
+    /// Allows or forbids other creators to fork a map
+    /// 
+    /// Only stops new forks - forks that were already started can still finish
+    /// copying their revision.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_metadata account
+    /// * `is_forkable` - Whether the map can be forked
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to update
+    /// * `user` - The map creator or a collaborator with the admin role
+    pub fn set_map_forkable(ctx: Context<SetMapForkable>, is_forkable: bool) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        map_metadata.require_admin(&ctx.accounts.user.key())?;
+
+        map_metadata.is_forkable = is_forkable;
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Creates a page of a map index
+    /// 
+    /// Permissionless - whoever needs a fresh page pays for it. Pages are walked
+    /// in order from 0 by map browsers.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_index account
+    /// * `kind` - Global index, category index or tag index
+    /// * `label` - The category or tag, empty for the global index
+    /// * `page` - Page number
+    /// 
+    /// # Accounts
+    /// * `map_index` - New page PDA derived from [MAP_INDEX_SEED, kind, label, page]
+    /// * `payer` - Pays for the account
+    /// * `system_program` - Solana system program
+    pub fn init_map_index_page(
+        ctx: Context<InitMapIndexPage>,
+        kind: MapIndexKind,
+        label: String,
+        page: u32,
+    ) -> Result<()> {
+        match kind {
+            MapIndexKind::All => require!(label.is_empty(), ErrorCode::InvalidMapLabel),
+            _ => validate_label(&label)?,
+        }
+
+        let map_index = &mut ctx.accounts.map_index;
+        map_index.kind = kind;
+        map_index.label = label;
+        map_index.page = page;
+        map_index.map_ids = Vec::new();
+
+        msg!("Map index {:?} '{}' page {} initialized", kind, map_index.label, page);
+        Ok(())
+    }
+
+    /// Sets a map's browse category and tags, such as "ctf", "small" or "1v1"
+    /// 
+    /// New maps have none. Only allowed while the map is not listed in the
+    /// category/tag indexes - unpublish it first, then publish again to list it
+    /// under the new labels.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_metadata account
+    /// * `category` - Browse category, empty for none
+    /// * `tags` - Up to MapMetadata::MAX_TAGS browse tags
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to update
+    /// * `user` - The map creator or a collaborator
+    pub fn update_map_labels(
+        ctx: Context<UpdateMapLabels>,
+        category: String,
+        tags: Vec<String>,
+    ) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        map_metadata.require_editor(&ctx.accounts.user.key())?;
+        require!(!map_metadata.is_listed, ErrorCode::MapListed);
 
+        map_metadata.set_labels(category, tags)?;
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
         Ok(())
     }
 
     /// Updates the metadata of an existing map
     /// 
-    /// Only the map creator can update their map's metadata.
+    /// Only the map creator and its collaborators can update the map's metadata.
     /// This allows changing the name and/or description without touching the actual map data.
     /// The updated_at timestamp is automatically refreshed.
     /// 
@@ -123,10 +446,10 @@ pub mod map_registry {
     /// 
     /// # Accounts
     /// * `map_metadata` - The map's metadata account to update
-    /// * `user` - Must be the original creator of the map
+    /// * `user` - The map creator or a collaborator
     /// 
     /// # Security
-    /// * Checks that the signer is the map creator before allowing updates
+    /// * Checks that the signer is the map creator or a collaborator before allowing updates
     pub fn update_map_metadata(
         ctx: Context<UpdateMapMetadata>,
         name: Option<String>,
@@ -134,11 +457,8 @@ pub mod map_registry {
     ) -> Result<()> {
         let map_metadata = &mut ctx.accounts.map_metadata;
         
-        // Only the creator can update their map
-        require!(
-            map_metadata.creator == ctx.accounts.user.key(),
-            ErrorCode::Unauthorized
-        );
+        // Only the creator and collaborators can update the map
+        map_metadata.require_editor(&ctx.accounts.user.key())?;
 
         // Update only the fields that were provided
         if let Some(new_name) = name {
@@ -156,41 +476,50 @@ pub mod map_registry {
 
     /// Updates the actual map data
     /// 
-    /// Only the map creator can update their map's data.
+    /// Only the map creator and its collaborators can update the map's data.
     /// This replaces the entire map data with new data.
     /// Uses reallocation to resize the account if the new data is a different size.
     /// 
+    /// Small maps only: both the old and new object lists are deserialized onto
+    /// the 32KB heap, and the account can only grow by 10KB per instruction.
+    /// Maps built with append_map_objects/write_map_objects should be edited with
+    /// the patch instructions (insert/remove/replace_map_objects, move_map_object) instead.
+    /// 
     /// # Arguments
     /// * `ctx` - The context containing map_metadata and map_data_account
     /// * `map_data` - The new map data to replace the existing data
     ///
     /// # Accounts
-    /// * `map_metadata` - Used to verify the creator (read-only)
+    /// * `map_metadata` - Used to verify the creator, spawn points are recounted
     /// * `map_data_account` - The data account to update (will be reallocated if needed)
-    /// * `user` - Must be the original creator of the map
+    /// * `user` - The map creator or a collaborator
     /// * `system_program` - Needed for reallocation
     ///
     /// # Security
-    /// * Checks that the signer is the map creator before allowing updates
+    /// * Checks that the signer is the map creator or a collaborator before allowing updates
     /// * Validates new data size is within limits
     pub fn update_map_data(
         ctx: Context<UpdateMapData>,
         map_data: Vec<MapObject>,
     ) -> Result<()> {
-        let map_metadata = &ctx.accounts.map_metadata;
+        let map_metadata = &mut ctx.accounts.map_metadata;
         let map_data_account = &mut ctx.accounts.map_data_account;
         
-        // Only the creator can update their map
-        require!(
-            map_metadata.creator == ctx.accounts.user.key(),
-            ErrorCode::Unauthorized
-        );
+        // Only the creator and collaborators can update the map
+        map_metadata.require_editor(&ctx.accounts.user.key())?;
+        require!(!map_metadata.is_fork_pending(), ErrorCode::ForkCopyPending);
         
         // Validate the new data size
         require!(
-            map_data.len() <= MapData::MAX_SIZE,
+            map_data.len() <= MapData::MAX_OBJECTS,
             ErrorCode::MapDataTooLarge
         );
+        validate_objects(&map_data, 0)?;
+
+        // Count the spawn points of the new objects
+        let bytes = map_data.try_to_vec()?;
+        map_metadata.count_spawn_points(&bytes[4..]);
+        map_metadata.require_playable_if_finalized()?;
 
         // Replace the old data with new data
         // The account is automatically resized via realloc in the Context
@@ -199,175 +528,1623 @@ pub mod map_registry {
         Ok(())
     }
 
-    /// Deletes a map and all its associated data
+    /// Appends objects to the end of a map
     /// 
-    /// Only the map creator can delete their map.
-    /// This instruction:
-    /// 1. Removes the map from the user's personal index
-    /// 2. Closes the map_metadata account (refunds rent to user)
-    /// 3. Closes the map_data_account (refunds rent to user)
+    /// Lets maps grow past what fits in a single transaction. The data account
+    /// is reallocated and the extra rent is paid by the creator.
     /// 
     /// # Arguments
-    /// * `ctx` - The context containing all required accounts
+    /// * `ctx` - The context containing map_metadata and map_data_account
+    /// * `objects` - The objects to add after the existing ones
     /// 
     /// # Accounts
-    /// * `map_metadata` - The metadata account to close
-    /// * `map_data_account` - The data account to close
-    /// * `user_map_index` - The user's index to remove the map from
-    /// * `user` - Must be the original creator (receives rent refund)
-    /// * `creator` - The creator's public key for validation
-    /// * `system_program` - Needed for closing accounts
+    /// * `map_metadata` - Used to verify the editor, spawn counts are updated
+    /// * `map_data_account` - The data account to grow
+    /// * `user` - The map creator or a collaborator
+    /// * `system_program` - Needed for the rent top-up
+    pub fn append_map_objects(
+        ctx: Context<WriteMapObjects>,
+        objects: Vec<MapObject>,
+    ) -> Result<()> {
+        write_objects(ctx, None, objects)
+    }
+
+    /// Overwrites objects of a map starting at `offset`
     /// 
-    /// # Security
-    /// * Checks that the signer is the map creator before allowing deletion
-    /// * Closes accounts to prevent them from being used again
-    pub fn delete_map(ctx: Context<DeleteMap>) -> Result<()> {
-        let map_metadata = &ctx.accounts.map_metadata;
-        let user_index = &mut ctx.accounts.user_map_index;
-        
-        // Only the creator can delete their map
+    /// Writing past the current end grows the map, so chunks can also be
+    /// uploaded out of order as long as no gap is left behind.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata and map_data_account
+    /// * `offset` - Index of the first object to overwrite, at most the current object count
+    /// * `objects` - The replacement objects
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - Used to verify the editor, spawn counts are updated
+    /// * `map_data_account` - The data account to write into
+    /// * `user` - The map creator or a collaborator
+    /// * `system_program` - Needed for the rent top-up
+    pub fn write_map_objects(
+        ctx: Context<WriteMapObjects>,
+        offset: u32,
+        objects: Vec<MapObject>,
+    ) -> Result<()> {
+        write_objects(ctx, Some(offset), objects)
+    }
+
+    /// Seals a draft map so it can be selected for games
+    /// 
+    /// The map becomes Unlisted: playable by id but not offered to public
+    /// lobbies until set_map_status publishes it. The map can still be edited
+    /// afterwards (changes reach games through publish_map_revision), but every
+    /// edit must leave it with enough spawn points.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata and map_data_account
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to finalize
+    /// * `map_data_account` - The uploaded map data (read-only)
+    /// * `user` - The map creator or a collaborator with the admin role
+    pub fn finalize_map(ctx: Context<FinalizeMap>) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        map_metadata.require_admin(&ctx.accounts.user.key())?;
+        require!(map_metadata.status == MapStatus::Draft, ErrorCode::MapFinalized);
+        require!(!map_metadata.is_fork_pending(), ErrorCode::ForkCopyPending);
+
+        // Read the objects straight from the account - large maps do not fit on the heap
+        let data = ctx.accounts.map_data_account.try_borrow_data()?;
+        let count = MapData::object_count(&data)?;
+        require!(count > 0, ErrorCode::EmptyMap);
+        map_metadata.require_playable()?;
+
+        map_metadata.status = MapStatus::Unlisted;
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!("Map {} finalized with {} objects", map_metadata.map_id, count);
+        Ok(())
+    }
+
+    /// Moves a finalized map between the published, unlisted and delisted states
+    /// 
+    /// * Published - listed and usable by public lobbies
+    /// * Unlisted - usable by id in private lobbies, hidden from listings
+    /// * Delisted - retired, no new lobbies can use it
+    /// 
+    /// Publishing needs a completed revision from publish_map_revision. Published
+    /// maps are listed in the index of their category and of each tag.
+    /// The index pages go in the remaining accounts, category first and then one
+    /// per tag in tag order: pages with room when publishing, the pages the map
+    /// is listed on when leaving Published.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_metadata account
+    /// * `status` - The new status, anything but Draft
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to update
+    /// * `user` - The map creator or a collaborator with the admin role
+    pub fn set_map_status(ctx: Context<SetMapStatus>, status: MapStatus) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        let index_pages = ctx.remaining_accounts;
+
+        map_metadata.require_admin(&ctx.accounts.user.key())?;
+        // Drafts only leave through finalize_map, and never go back
+        require!(map_metadata.is_finalized(), ErrorCode::MapNotFinalized);
+        require!(status != MapStatus::Draft, ErrorCode::InvalidStatusTransition);
+
+        if status == MapStatus::Published {
+            map_metadata.require_playable()?;
+            // Public lobbies play the published revision, so there must be one
+            require!(map_metadata.published_version > 0, ErrorCode::NoPublishedRevision);
+            if !map_metadata.is_listed {
+                list_map(map_metadata, index_pages)?;
+            }
+        } else if map_metadata.is_listed {
+            unlist_map(map_metadata, index_pages)?;
+        }
+
+        msg!(
+            "Map {} status {:?} -> {:?}",
+            map_metadata.map_id,
+            map_metadata.status,
+            status
+        );
+        map_metadata.status = status;
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Inserts objects at `index`, shifting the following objects back
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata and map_data_account
+    /// * `index` - Position of the first inserted object, at most the current object count
+    /// * `objects` - The objects to insert
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - Used to verify the creator, updated_at is refreshed
+    /// * `map_data_account` - The data account to grow
+    /// * `user` - The map creator or a collaborator, pays the extra rent
+    /// * `system_program` - Needed for the rent top-up
+    pub fn insert_map_objects(
+        mut ctx: Context<EditMapObjects>,
+        index: u32,
+        objects: Vec<MapObject>,
+    ) -> Result<()> {
+        let count = begin_map_edit(&mut ctx)?;
+        let index = index as usize;
+        require!(index <= count, ErrorCode::ObjectIndexOutOfBounds);
+        validate_objects(&objects, index)?;
+
+        let new_count = count + objects.len();
+        require!(new_count <= MapData::MAX_OBJECTS, ErrorCode::MapDataTooLarge);
+
+        let map_data_info = ctx.accounts.map_data_account.to_account_info();
+        resize_map_data(
+            &map_data_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_count,
+        )?;
+
+        {
+            // Shift the tail back, then drop the new objects into the gap
+            let mut data = map_data_info.try_borrow_mut_data()?;
+            let start = MapData::HEADER_SIZE + index * MapData::OBJECT_SIZE;
+            let old_end = MapData::HEADER_SIZE + count * MapData::OBJECT_SIZE;
+            let gap = objects.len() * MapData::OBJECT_SIZE;
+            data.copy_within(start..old_end, start + gap);
+
+            let bytes = objects.try_to_vec()?;
+            data[start..start + gap].copy_from_slice(&bytes[4..]);
+            ctx.accounts.map_metadata.track_added(&bytes[4..]);
+        }
+
+        finish_map_edit(&mut ctx)
+    }
+
+    /// Removes `remove_count` objects starting at `start`, shifting the following objects forward
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata and map_data_account
+    /// * `start` - Index of the first object to remove
+    /// * `remove_count` - Number of objects to remove
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - Used to verify the creator, updated_at is refreshed
+    /// * `map_data_account` - The data account to shrink
+    /// * `user` - The map creator or a collaborator, receives the freed rent
+    /// * `system_program` - Needed for reallocation
+    pub fn remove_map_objects(
+        mut ctx: Context<EditMapObjects>,
+        start: u32,
+        remove_count: u32,
+    ) -> Result<()> {
+        let count = begin_map_edit(&mut ctx)?;
+        let (start, remove_count) = (start as usize, remove_count as usize);
         require!(
-            map_metadata.creator == ctx.accounts.user.key(),
-            ErrorCode::Unauthorized
+            remove_count > 0 && start + remove_count <= count,
+            ErrorCode::ObjectIndexOutOfBounds
         );
 
-        // Remove the map from the user's personal index
-        // This maintains the integrity of the user's map list
-        let map_id = &map_metadata.map_id;
-        if let Some(index) = user_index.map_ids[0..user_index.map_count as usize]
-            .iter()
-            .position(|id| id == map_id) 
+        let map_data_info = ctx.accounts.map_data_account.to_account_info();
         {
-            // Remove the map_id from the vector
-            user_index.map_ids.remove(index);
-            user_index.map_count -= 1;
+            // Close the gap before the account is truncated
+            let mut data = map_data_info.try_borrow_mut_data()?;
+            let to = MapData::HEADER_SIZE + start * MapData::OBJECT_SIZE;
+            let from = MapData::HEADER_SIZE + (start + remove_count) * MapData::OBJECT_SIZE;
+            let old_end = MapData::HEADER_SIZE + count * MapData::OBJECT_SIZE;
+            ctx.accounts.map_metadata.track_removed(&data[to..from]);
+            data.copy_within(from..old_end, to);
         }
+        resize_map_data(
+            &map_data_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            count - remove_count,
+        )?;
 
-        // The accounts are automatically closed due to the 'close' constraint
-        // This refunds the rent to the user
-        Ok(())
+        finish_map_edit(&mut ctx)
     }
-}
 
-// ============================================================================
-// Account Contexts (define which accounts each instruction needs)
-// ============================================================================
+    /// Replaces individual objects in place
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata and map_data_account
+    /// * `patches` - Index and new value of each object to replace
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - Used to verify the creator, updated_at is refreshed
+    /// * `map_data_account` - The data account to write into
+    /// * `user` - The map creator or a collaborator
+    /// * `system_program` - Unused, shared context with the other patch instructions
+    pub fn replace_map_objects(
+        mut ctx: Context<EditMapObjects>,
+        patches: Vec<MapObjectPatch>,
+    ) -> Result<()> {
+        let count = begin_map_edit(&mut ctx)?;
 
-/// Context for initializing the global map registry
-#[derive(Accounts)]
-pub struct InitializeMapRegistry<'info> {
+        {
+            let mut data = ctx.accounts.map_data_account.try_borrow_mut_data()?;
+            for patch in &patches {
+                let index = patch.index as usize;
+                require!(index < count, ErrorCode::ObjectIndexOutOfBounds);
+                patch.object.validate(index)?;
+
+                let start = MapData::HEADER_SIZE + index * MapData::OBJECT_SIZE;
+                let bytes = patch.object.try_to_vec()?;
+                ctx.accounts.map_metadata.track_removed(&data[start..start + MapData::OBJECT_SIZE]);
+                data[start..start + MapData::OBJECT_SIZE].copy_from_slice(&bytes);
+                ctx.accounts.map_metadata.track_added(&bytes);
+            }
+        }
+
+        finish_map_edit(&mut ctx)
+    }
+
+    /// Moves one object to a new position, shifting the objects in between
+    /// 
+    /// Object order decides draw order on some clients, this avoids a remove + insert.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata and map_data_account
+    /// * `from` - Current index of the object
+    /// * `to` - Index the object ends up at
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - Used to verify the creator, updated_at is refreshed
+    /// * `map_data_account` - The data account to reorder
+    /// * `user` - The map creator or a collaborator
+    /// * `system_program` - Unused, shared context with the other patch instructions
+    pub fn move_map_object(mut ctx: Context<EditMapObjects>, from: u32, to: u32) -> Result<()> {
+        let count = begin_map_edit(&mut ctx)?;
+        let (from, to) = (from as usize, to as usize);
+        require!(from < count && to < count, ErrorCode::ObjectIndexOutOfBounds);
+
+        {
+            let mut data = ctx.accounts.map_data_account.try_borrow_mut_data()?;
+            let offset = |index: usize| MapData::HEADER_SIZE + index * MapData::OBJECT_SIZE;
+            if from < to {
+                data[offset(from)..offset(to + 1)].rotate_left(MapData::OBJECT_SIZE);
+            } else {
+                data[offset(to)..offset(from + 1)].rotate_right(MapData::OBJECT_SIZE);
+            }
+        }
+
+        finish_map_edit(&mut ctx)
+    }
+
+    /// Starts publishing the current map contents as a new immutable revision
+    /// 
+    /// Games pin a published revision, so later edits never change a match in
+    /// progress. The objects are copied over with copy_revision_objects, as an
+    /// account can only grow by 10KB per instruction. New lobbies keep using the
+    /// previous revision until the copy completes.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata, map_data_account and map_revision
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to publish, its version is bumped
+    /// * `map_data_account` - The current map data (read-only)
+    /// * `map_revision` - New PDA for the snapshot, derived from [MAP_REVISION_SEED, map_id, nonce, version]
+    /// * `user` - The map creator or a collaborator with the admin role, pays for the snapshot
+    /// * `system_program` - Solana system program
+    pub fn publish_map_revision(ctx: Context<PublishMapRevision>) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        let map_revision = &mut ctx.accounts.map_revision;
+
+        map_metadata.require_admin(&ctx.accounts.user.key())?;
+        require!(map_metadata.is_finalized(), ErrorCode::MapNotFinalized);
+        // Edits since finalizing may have removed spawn points
+        map_metadata.require_playable()?;
+
+        let object_count = MapData::object_count(&ctx.accounts.map_data_account.try_borrow_data()?)?;
+
+        map_metadata.version += 1;
+
+        map_revision.map_metadata = map_metadata.key();
+        map_revision.version = map_metadata.version;
+        map_revision.published_at = Clock::get()?.unix_timestamp;
+        map_revision.expect_objects(object_count);
+
+        msg!(
+            "Publishing map {} version {} ({} objects)",
+            map_metadata.map_id,
+            map_revision.version,
+            object_count
+        );
+        Ok(())
+    }
+
+    /// Copies the next chunk of objects into a revision that is being published
+    /// 
+    /// If the map was edited since publishing started, the copy restarts from
+    /// the new contents: straight away when objects were added or removed, after
+    /// the last chunk when objects were changed in place. The finished snapshot
+    /// is hashed once, and becomes the map's content hash when it goes live.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata, map_data_account and map_revision
+    /// * `max_objects` - Upper bound on objects to copy in this instruction
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map being published, published_version advances once the copy completes
+    /// * `map_data_account` - The current map data (read-only)
+    /// * `map_revision` - The snapshot to fill
+    /// * `user` - The map creator or a collaborator with the admin role, pays the extra rent
+    /// * `system_program` - Needed for the rent top-up
+    pub fn copy_revision_objects(
+        ctx: Context<CopyRevisionObjects>,
+        max_objects: u32,
+    ) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        let map_revision = &mut ctx.accounts.map_revision;
+
+        map_metadata.require_admin(&ctx.accounts.user.key())?;
+        require!(!map_revision.is_complete(), ErrorCode::RevisionComplete);
+
+        let map_data = ctx.accounts.map_data_account.try_borrow_data()?;
+        let object_count = MapData::object_count(&map_data)?;
+
+        // Objects were added or removed - start over from the new contents
+        if object_count != map_revision.object_count as usize {
+            map_revision.expect_objects(object_count);
+            msg!("Map changed while publishing, restarting copy");
+        }
+
+        let copied = map_revision.copied_count as usize;
+        let chunk = (max_objects as usize)
+            .min(object_count - copied)
+            .min(MapRevision::MAX_OBJECTS_PER_COPY);
+        let new_copied = copied + chunk;
+
+        // Size the revision for the objects copied so far
+        let revision_info = map_revision.to_account_info();
+        resize_account(
+            &revision_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            MapRevision::HEADER_SIZE + new_copied * MapData::OBJECT_SIZE,
+        )?;
+
+        {
+            let source_start = MapData::HEADER_SIZE + copied * MapData::OBJECT_SIZE;
+            let target_start = MapRevision::HEADER_SIZE + copied * MapData::OBJECT_SIZE;
+            let len = chunk * MapData::OBJECT_SIZE;
+            revision_info.try_borrow_mut_data()?[target_start..target_start + len]
+                .copy_from_slice(&map_data[source_start..source_start + len]);
+        }
+        map_revision.copied_count = new_copied as u32;
+
+        if map_revision.is_complete() {
+            // Objects changed in place only show up once everything is copied
+            let len = object_count * MapData::OBJECT_SIZE;
+            let revision_data = revision_info.try_borrow_data()?;
+            let snapshot = &revision_data[MapRevision::HEADER_SIZE..MapRevision::HEADER_SIZE + len];
+            if snapshot != &map_data[MapData::HEADER_SIZE..MapData::HEADER_SIZE + len] {
+                map_revision.expect_objects(object_count);
+                msg!("Map changed while publishing, restarting copy");
+            } else {
+                // The one time the whole object list is hashed
+                map_revision.seal(hash(snapshot).to_bytes(), map_metadata);
+            }
+        }
+
+        // Only complete revisions become the one new lobbies use
+        if map_revision.is_complete() && map_revision.version > map_metadata.published_version {
+            map_metadata.published_version = map_revision.version;
+            map_metadata.content_hash = map_revision.content_hash;
+            msg!("Map {} version {} is live", map_metadata.map_id, map_revision.version);
+        }
+
+        msg!(
+            "Map revision {} - copied {}/{} objects",
+            map_revision.version,
+            new_copied,
+            object_count
+        );
+        Ok(())
+    }
+
+    /// Rates a map from 1 to 5 stars and optionally likes it
+    /// 
+    /// One vote per wallet per map - rating again replaces the previous vote.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata and map_rating
+    /// * `stars` - Rating from 1 to 5
+    /// * `liked` - Whether the user likes the map
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The rated map, its aggregates are updated
+    /// * `map_rating` - The user's vote PDA, derived from [MAP_RATING_SEED, map_metadata, user]
+    /// * `user` - The voter, cannot be the map creator or a collaborator
+    /// * `system_program` - Solana system program
+    pub fn rate_map(ctx: Context<RateMap>, stars: u8, liked: bool) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        let map_rating = &mut ctx.accounts.map_rating;
+
+        require!(
+            map_metadata.creator != ctx.accounts.user.key()
+                && map_metadata.role_of(&ctx.accounts.user.key()).is_none(),
+            ErrorCode::CannotRateOwnMap
+        );
+        require!(map_metadata.is_finalized(), ErrorCode::MapNotFinalized);
+        require!((1..=5).contains(&stars), ErrorCode::InvalidRating);
+
+        // Take back the previous vote before counting the new one
+        if map_rating.rater == ctx.accounts.user.key() {
+            map_metadata.rating_count = map_metadata.rating_count.saturating_sub(1);
+            map_metadata.rating_total = map_metadata.rating_total.saturating_sub(map_rating.stars as u64);
+            if map_rating.liked {
+                map_metadata.like_count = map_metadata.like_count.saturating_sub(1);
+            }
+        }
+
+        map_rating.map_metadata = map_metadata.key();
+        map_rating.rater = ctx.accounts.user.key();
+        map_rating.stars = stars;
+        map_rating.liked = liked;
+        map_rating.rated_at = Clock::get()?.unix_timestamp;
+
+        map_metadata.rating_count += 1;
+        map_metadata.rating_total += stars as u64;
+        if liked {
+            map_metadata.like_count += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Counts a match played on the map
+    /// 
+    /// CPI only - signed by the matchmaking program's map play PDA when a game starts.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing map_metadata
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The played map
+    /// * `play_authority` - Matchmaking PDA derived from [MAP_PLAY_AUTHORITY_SEED]
+    pub fn record_map_play(ctx: Context<RecordMapPlay>) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        map_metadata.play_count += 1;
+        Ok(())
+    }
+
+    /// Adds a collaborator to a map, or changes their role
+    /// 
+    /// Editors can change the map's data and metadata. Admins can also manage
+    /// collaborators, finalize, publish and change the map's status. Only the
+    /// creator can delete or transfer the map.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_metadata account
+    /// * `collaborator` - Wallet to add
+    /// * `role` - Editor or Admin
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to update
+    /// * `user` - The map creator or an admin collaborator
+    pub fn set_map_collaborator(
+        ctx: Context<ManageMapCollaborators>,
+        collaborator: Pubkey,
+        role: MapRole,
+    ) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        map_metadata.require_admin(&ctx.accounts.user.key())?;
+        require!(collaborator != map_metadata.creator, ErrorCode::InvalidCollaborator);
+
+        if let Some(existing) = map_metadata
+            .collaborators
+            .iter_mut()
+            .find(|c| c.wallet == collaborator)
+        {
+            existing.role = role;
+        } else {
+            require!(
+                map_metadata.collaborators.len() < MapMetadata::MAX_COLLABORATORS,
+                ErrorCode::TooManyCollaborators
+            );
+            map_metadata.collaborators.push(MapCollaborator { wallet: collaborator, role });
+        }
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!("Map {} collaborator {} is now {:?}", map_metadata.map_id, collaborator, role);
+        Ok(())
+    }
+
+    /// Removes a collaborator from a map
+    /// 
+    /// Collaborators can also remove themselves.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_metadata account
+    /// * `collaborator` - Wallet to remove
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to update
+    /// * `user` - The map creator, an admin collaborator or the collaborator themselves
+    pub fn remove_map_collaborator(
+        ctx: Context<ManageMapCollaborators>,
+        collaborator: Pubkey,
+    ) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        let user = ctx.accounts.user.key();
+
+        if user != collaborator {
+            map_metadata.require_admin(&user)?;
+        }
+
+        let pos = map_metadata
+            .collaborators
+            .iter()
+            .position(|c| c.wallet == collaborator)
+            .ok_or(ErrorCode::InvalidCollaborator)?;
+        map_metadata.collaborators.remove(pos);
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!("Map {} collaborator {} removed", map_metadata.map_id, collaborator);
+        Ok(())
+    }
+
+    /// Offers a map to another wallet
+    /// 
+    /// Nothing moves until the new owner calls accept_map_ownership, so no one
+    /// can be handed maps they did not ask for. A new offer replaces the open
+    /// one, offering to the default pubkey withdraws it.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_metadata account
+    /// * `new_owner` - Wallet that may accept the map
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to transfer
+    /// * `user` - Must be the current creator
+    pub fn transfer_map_ownership(
+        ctx: Context<TransferMapOwnership>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+
+        require!(
+            map_metadata.creator == ctx.accounts.user.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(new_owner != map_metadata.creator, ErrorCode::InvalidCollaborator);
+
+        map_metadata.pending_owner = new_owner;
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!("Map {} offered to {}", map_metadata.map_id, new_owner);
+        Ok(())
+    }
+
+    /// Accepts a map offered with transfer_map_ownership
+    /// 
+    /// Moves the map from the current creator's UserMapIndex to the new owner's.
+    /// The previous creator loses all rights unless added back as a collaborator.
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing the map_metadata and both user indexes
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The map to take over
+    /// * `from_map_index` - The current creator's personal index
+    /// * `to_map_index` - The new owner's personal index, created if needed
+    /// * `user` - Must be the pending owner, pays for their index if needed
+    /// * `system_program` - Solana system program
+    pub fn accept_map_ownership(ctx: Context<AcceptMapOwnership>) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        let from_index = &mut ctx.accounts.from_map_index;
+        let to_index = &mut ctx.accounts.to_map_index;
+        let new_owner = ctx.accounts.user.key();
+
+        require!(
+            map_metadata.pending_owner != Pubkey::default()
+                && map_metadata.pending_owner == new_owner,
+            ErrorCode::NoPendingTransfer
+        );
+
+        // Move the map between the two personal indexes
+        let map_id = &map_metadata.map_id;
+        if let Some(index) = from_index.map_ids.iter().position(|id| id == map_id) {
+            from_index.map_ids.remove(index);
+            from_index.map_count -= 1;
+        }
+        require!(
+            to_index.map_ids.len() < 100,
+            ErrorCode::UserMapLimitReached
+        );
+        to_index.owner = new_owner;
+        to_index.map_ids.push(map_id.clone());
+        to_index.map_count += 1;
+
+        // The new owner no longer needs a collaborator entry
+        map_metadata.collaborators.retain(|c| c.wallet != new_owner);
+        map_metadata.creator = new_owner;
+        map_metadata.pending_owner = Pubkey::default();
+        map_metadata.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!("Map {} transferred to {}", map_metadata.map_id, new_owner);
+        Ok(())
+    }
+
+    /// Deletes a map and all its associated data
+    /// 
+    /// Only the map creator can delete their map.
+    /// This instruction:
+    /// 1. Removes the map from the user's personal index
+    /// 2. Closes the map_metadata account (refunds rent to user)
+    /// 3. Closes the map_data_account (refunds rent to user)
+    /// 
+    /// # Arguments
+    /// * `ctx` - The context containing all required accounts
+    /// 
+    /// # Accounts
+    /// * `map_metadata` - The metadata account to close
+    /// * `map_data_account` - The data account to close
+    /// * `user_map_index` - The user's index to remove the map from
+    /// * `map_index` - The global index page the map is on
+    /// * `user` - Must be the original creator (receives rent refund)
+    /// * `creator` - The creator's public key for validation
+    /// * `system_program` - Needed for closing accounts
+    /// 
+    /// Published maps also need the category and tag index pages they are
+    /// listed on in the remaining accounts, as for set_map_status.
+    /// 
+    /// Revisions stay open so matches pinned to them can still load the map. A
+    /// map recreated under the same map_id gets a new nonce, so its revisions
+    /// never collide with the old ones.
+    /// 
+    /// # Security
+    /// * Checks that the signer is the map creator before allowing deletion
+    /// * Default maps cannot be deleted until the admin demotes them
+    /// * Closes accounts to prevent them from being used again
+    pub fn delete_map(ctx: Context<DeleteMap>) -> Result<()> {
+        let map_metadata = &mut ctx.accounts.map_metadata;
+        let user_index = &mut ctx.accounts.user_map_index;
+        
+        // Only the creator can delete their map
+        require!(
+            map_metadata.creator == ctx.accounts.user.key(),
+            ErrorCode::Unauthorized
+        );
+
+        // Official maps must be demoted by the admin before they can go away
+        require!(!map_metadata.is_default, ErrorCode::DefaultMapProtected);
+
+        // Remove the map from the user's personal index
+        // This maintains the integrity of the user's map list
+        let map_id = &map_metadata.map_id;
+        if let Some(index) = user_index.map_ids[0..user_index.map_count as usize]
+            .iter()
+            .position(|id| id == map_id) 
+        {
+            // Remove the map_id from the vector
+            user_index.map_ids.remove(index);
+            user_index.map_count -= 1;
+        }
+
+        // Remove the map from the browse indexes
+        ctx.accounts.map_index.remove(&map_metadata.map_id);
+        if map_metadata.is_listed {
+            unlist_map(map_metadata, ctx.remaining_accounts)?;
+        }
+
+        // Close the data account by hand - deserializing a large map would exhaust the heap
+        let map_data_info = ctx.accounts.map_data_account.to_account_info();
+        let user_info = ctx.accounts.user.to_account_info();
+        **user_info.try_borrow_mut_lamports()? += map_data_info.lamports();
+        **map_data_info.try_borrow_mut_lamports()? = 0;
+        map_data_info.assign(&system_program::ID);
+        map_data_info.realloc(0, false)?;
+
+        // The metadata account is automatically closed due to the 'close' constraint
+        // This refunds the rent to the user
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Shared body of append_map_objects and write_map_objects
+/// Writes serialized objects directly into the account data so the whole map
+/// never has to be deserialized
+fn write_objects(
+    ctx: Context<WriteMapObjects>,
+    offset: Option<u32>,
+    objects: Vec<MapObject>,
+) -> Result<()> {
+    let map_metadata = &mut ctx.accounts.map_metadata;
+
+    // Only the creator and collaborators can edit the map
+    map_metadata.require_editor(&ctx.accounts.user.key())?;
+    require!(!map_metadata.is_fork_pending(), ErrorCode::ForkCopyPending);
+
+    let map_data_info = ctx.accounts.map_data_account.to_account_info();
+    let count = MapData::object_count(&map_data_info.try_borrow_data()?)?;
+    let offset = offset.map_or(count, |offset| offset as usize);
+    require!(offset <= count, ErrorCode::InvalidWriteOffset);
+
+    let new_count = count.max(offset + objects.len());
+    require!(new_count <= MapData::MAX_OBJECTS, ErrorCode::MapDataTooLarge);
+    validate_objects(&objects, offset)?;
+
+    // Grow the account, the creator pays the extra rent
+    if new_count > count {
+        resize_map_data(
+            &map_data_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_count,
+        )?;
+    }
+
+    // Borsh puts a 4 byte length before the objects, skip it
+    let bytes = objects.try_to_vec()?;
+    let start = MapData::HEADER_SIZE + offset * MapData::OBJECT_SIZE;
+    let overwritten = (count.min(offset + objects.len()) - offset) * MapData::OBJECT_SIZE;
+    {
+        // Only the objects this chunk replaces are counted, never the whole map
+        let mut data = map_data_info.try_borrow_mut_data()?;
+        map_metadata.track_removed(&data[start..start + overwritten]);
+        data[start..start + bytes.len() - 4].copy_from_slice(&bytes[4..]);
+        data[8..MapData::HEADER_SIZE].copy_from_slice(&(new_count as u32).to_le_bytes());
+    }
+    map_metadata.track_added(&bytes[4..]);
+    map_metadata.require_playable_if_finalized()?;
+
+    msg!("Wrote {} objects at {} - map now has {}", objects.len(), offset, new_count);
+    Ok(())
+}
+
+/// Blue and red spawn points among serialized objects (without the Vec length prefix)
+/// Only the model type byte of each object is read
+fn spawn_point_counts(object_bytes: &[u8]) -> (u16, u16) {
+    let count = |model_type: ModelType| {
+        object_bytes
+            .chunks_exact(MapData::OBJECT_SIZE)
+            .filter(|object| object[0] == model_type as u8)
+            .count() as u16
+    };
+    (count(ModelType::SpawnPointBlue), count(ModelType::SpawnPointRed))
+}
+
+/// Add a new map to its creator's personal index and to a global index page
+fn index_new_map(
+    map_metadata: &mut MapMetadata,
+    user_index: &mut UserMapIndex,
+    map_index: &mut MapIndexPage,
+) -> Result<()> {
+    // This allows users to easily query all maps they've created
+    require!(
+        user_index.map_ids.len() < 100,
+        ErrorCode::UserMapLimitReached
+    );
+    user_index.map_ids.push(map_metadata.map_id.clone());
+    user_index.map_count += 1;
+
+    require!(map_index.kind == MapIndexKind::All, ErrorCode::InvalidMapIndexPage);
+    map_index.insert(map_metadata.map_id.clone())?;
+    map_metadata.index_page = map_index.page;
+    Ok(())
+}
+
+/// Category and tag indexes a map belongs in, category first then tags in order
+fn label_indexes(map_metadata: &MapMetadata) -> Vec<(MapIndexKind, &str)> {
+    let category = (!map_metadata.category.is_empty())
+        .then_some((MapIndexKind::Category, map_metadata.category.as_str()));
+    category
+        .into_iter()
+        .chain(map_metadata.tags.iter().map(|tag| (MapIndexKind::Tag, tag.as_str())))
+        .collect()
+}
+
+/// Load an index page passed as a remaining account and check it is the page
+/// of `kind`/`label`
+fn load_index_page(
+    info: &AccountInfo,
+    kind: MapIndexKind,
+    label: &str,
+) -> Result<MapIndexPage> {
+    require!(info.owner == &crate::ID, ErrorCode::InvalidMapIndexPage);
+    let page = MapIndexPage::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require!(
+        page.kind == kind && page.label == label && info.key() == page.address(),
+        ErrorCode::InvalidMapIndexPage
+    );
+    Ok(page)
+}
+
+fn save_index_page(info: &AccountInfo, page: &MapIndexPage) -> Result<()> {
+    page.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+/// Add a map to the index of its category and of each tag
+/// `index_pages` holds one page with room per index, in label_indexes order
+fn list_map(map_metadata: &mut MapMetadata, index_pages: &[AccountInfo]) -> Result<()> {
+    let indexes = label_indexes(map_metadata);
+    require!(index_pages.len() == indexes.len(), ErrorCode::MissingMapIndexPage);
+
+    let mut category_page = 0;
+    let mut tag_pages = Vec::with_capacity(map_metadata.tags.len());
+    for ((kind, label), info) in indexes.into_iter().zip(index_pages) {
+        let mut page = load_index_page(info, kind, label)?;
+        page.insert(map_metadata.map_id.clone())?;
+        save_index_page(info, &page)?;
+
+        match kind {
+            MapIndexKind::Category => category_page = page.page,
+            _ => tag_pages.push(page.page),
+        }
+    }
+    map_metadata.category_page = category_page;
+    map_metadata.tag_pages = tag_pages;
+    map_metadata.is_listed = true;
+    Ok(())
+}
+
+/// Remove a map from the category and tag index pages it was listed on
+/// `index_pages` holds those pages, in label_indexes order
+fn unlist_map(map_metadata: &mut MapMetadata, index_pages: &[AccountInfo]) -> Result<()> {
+    let indexes = label_indexes(map_metadata);
+    require!(index_pages.len() == indexes.len(), ErrorCode::MissingMapIndexPage);
+
+    let mut tag_pages = map_metadata.tag_pages.iter();
+    for ((kind, label), info) in indexes.into_iter().zip(index_pages) {
+        let mut page = load_index_page(info, kind, label)?;
+        let expected_page = match kind {
+            MapIndexKind::Category => Some(&map_metadata.category_page),
+            _ => tag_pages.next(),
+        };
+        require!(expected_page == Some(&page.page), ErrorCode::InvalidMapIndexPage);
+
+        page.remove(&map_metadata.map_id);
+        save_index_page(info, &page)?;
+    }
+    map_metadata.tag_pages = Vec::new();
+    map_metadata.is_listed = false;
+    Ok(())
+}
+
+/// Categories and tags are short lowercase slugs such as "ctf", "small" or "1v1"
+fn validate_label(label: &str) -> Result<()> {
+    require!(
+        !label.is_empty()
+            && label.len() <= MapIndexPage::MAX_LABEL_LEN
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-'),
+        ErrorCode::InvalidMapLabel
+    );
+    Ok(())
+}
+
+/// Validate every object, `first_index` is the map index of `objects[0]` for error reports
+fn validate_objects(objects: &[MapObject], first_index: usize) -> Result<()> {
+    for (i, object) in objects.iter().enumerate() {
+        object.validate(first_index + i)?;
+    }
+    Ok(())
+}
+
+/// Resize a MapData account to hold exactly `new_count` objects and set its length prefix
+/// Growing charges the extra rent to `user`, shrinking refunds the excess to them
+fn resize_map_data<'info>(
+    map_data_info: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_count: usize,
+) -> Result<()> {
+    let new_len = MapData::HEADER_SIZE + new_count * MapData::OBJECT_SIZE;
+    resize_account(map_data_info, user, system_program, new_len)?;
+    map_data_info.try_borrow_mut_data()?[8..MapData::HEADER_SIZE]
+        .copy_from_slice(&(new_count as u32).to_le_bytes());
+    Ok(())
+}
+
+/// Realloc a program account to `new_len`, keeping it exactly rent exempt
+/// Growing charges the extra rent to `user`, shrinking refunds the excess to them
+fn resize_account<'info>(
+    info: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let rent_exempt = Rent::get()?.minimum_balance(new_len);
+    let lamports = info.lamports();
+
+    if rent_exempt > lamports {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: user.clone(),
+                    to: info.clone(),
+                },
+            ),
+            rent_exempt - lamports,
+        )?;
+    } else if lamports > rent_exempt {
+        **info.try_borrow_mut_lamports()? = rent_exempt;
+        **user.try_borrow_mut_lamports()? += lamports - rent_exempt;
+    }
+
+    info.realloc(new_len, false)?;
+    Ok(())
+}
+
+/// Checks shared by the patch instructions: creator or collaborator only, bumps updated_at
+fn begin_map_edit(ctx: &mut Context<EditMapObjects>) -> Result<usize> {
+    let map_metadata = &mut ctx.accounts.map_metadata;
+    map_metadata.require_editor(&ctx.accounts.user.key())?;
+    require!(!map_metadata.is_fork_pending(), ErrorCode::ForkCopyPending);
+    map_metadata.updated_at = Clock::get()?.unix_timestamp;
+
+    MapData::object_count(&ctx.accounts.map_data_account.try_borrow_data()?)
+}
+
+/// Finalized maps must keep enough spawn points after a patch
+fn finish_map_edit(ctx: &mut Context<EditMapObjects>) -> Result<()> {
+    ctx.accounts.map_metadata.require_playable_if_finalized()
+}
+
+// ============================================================================
+// Account Contexts (define which accounts each instruction needs)
+// ============================================================================
+
+/// Context for initializing the global map registry
+#[derive(Accounts)]
+pub struct InitializeMapRegistry<'info> {
     /// The global registry PDA that tracks all maps
-    /// Uses init_if_needed so it can be called multiple times safely
+    /// Can only be created once, by the upgrade authority
+    #[account(
+        init,
+        payer = user,
+        space = 8 + MapRegistry::INIT_SPACE,
+        seeds = [MAP_REGISTRY_SEED],
+        bump
+    )]
+    pub map_registry: Account<'info, MapRegistry>,
+
+    /// This program's ProgramData account
+    /// Keeps whoever lands first at deploy from claiming the admin role
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(user.key()) @ ErrorCode::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    /// The upgrade authority, pays for the account creation
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// Solana system program (required for creating accounts)
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for creating a new map
+#[derive(Accounts)]
+#[instruction(map_id: String, name: String, description: String, is_default: bool, map_data: Vec<MapObject>)]
+pub struct CreateMap<'info> {
+    /// New PDA account to store map metadata (name, creator, timestamps, etc.)
+    /// Derived from [MAP_METADATA_SEED, map_id]
+    #[account(
+        init,
+        payer = user,
+        space = 8 + MapMetadata::INIT_SPACE,
+        seeds = [MAP_METADATA_SEED, map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+    
+    /// New PDA account to store the actual map data (level layout, objects, etc.)
+    /// Derived from [MAP_DATA_SEED, map_id]
+    /// Space is calculated dynamically based on the size of map_data
+    /// Each MapObject is 19 bytes: 1 (enum) + 6 (pos) + 6 (rot) + 3 (scale) + 3 (color)
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 4 + (map_data.len() * 19), // 8 (discriminator) + 4 (vec length) + (objects * 19 bytes each)
+        seeds = [MAP_DATA_SEED, map_id.as_bytes()],
+        bump
+    )]
+    pub map_data_account: Account<'info, MapData>,
+    
+    /// The global registry to update counters
+    #[account(
+        mut,
+        seeds = [MAP_REGISTRY_SEED],
+        bump
+    )]
+    pub map_registry: Account<'info, MapRegistry>,
+    
+    /// The user's personal index of maps they've created
+    /// Uses init_if_needed for first-time map creators
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserMapIndex::INIT_SPACE,
+        seeds = [USER_MAP_INDEX_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub user_map_index: Account<'info, UserMapIndex>,
+
+    /// A page of the global map index with room left
+    #[account(
+        mut,
+        seeds = [MAP_INDEX_SEED, &[map_index.kind as u8], map_index.label.as_bytes(), &map_index.page.to_le_bytes()],
+        bump
+    )]
+    pub map_index: Account<'info, MapIndexPage>,
+    
+    /// The map creator and transaction signer
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    /// Solana system program
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for starting a fork
+#[derive(Accounts)]
+#[instruction(map_id: String)]
+pub struct ForkMap<'info> {
+    /// The map being forked
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, parent_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub parent_metadata: Account<'info, MapMetadata>,
+
+    /// The parent's latest published revision, the fork's source
+    #[account(
+        seeds = [
+            MAP_REVISION_SEED,
+            parent_metadata.map_id.as_bytes(),
+            &parent_metadata.nonce.to_le_bytes(),
+            &parent_metadata.published_version.to_le_bytes()
+        ],
+        bump
+    )]
+    pub parent_revision: Account<'info, MapRevision>,
+
+    /// New PDA for the fork's metadata
+    /// Derived from [MAP_METADATA_SEED, map_id]
+    #[account(
+        init,
+        payer = user,
+        space = 8 + MapMetadata::INIT_SPACE,
+        seeds = [MAP_METADATA_SEED, map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// New PDA for the fork's data, grown by copy_fork_objects
+    /// Derived from [MAP_DATA_SEED, map_id]
+    #[account(
+        init,
+        payer = user,
+        space = MapData::HEADER_SIZE,
+        seeds = [MAP_DATA_SEED, map_id.as_bytes()],
+        bump
+    )]
+    pub map_data_account: Account<'info, MapData>,
+
+    /// The global registry to update counters
+    #[account(
+        mut,
+        seeds = [MAP_REGISTRY_SEED],
+        bump
+    )]
+    pub map_registry: Account<'info, MapRegistry>,
+
+    /// The user's personal index of maps they've created
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserMapIndex::INIT_SPACE,
+        seeds = [USER_MAP_INDEX_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub user_map_index: Account<'info, UserMapIndex>,
+
+    /// A page of the global map index with room left
+    #[account(
+        mut,
+        seeds = [MAP_INDEX_SEED, &[map_index.kind as u8], map_index.label.as_bytes(), &map_index.page.to_le_bytes()],
+        bump
+    )]
+    pub map_index: Account<'info, MapIndexPage>,
+
+    /// The fork's creator
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Solana system program
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for copying objects into a fork
+#[derive(Accounts)]
+pub struct CopyForkObjects<'info> {
+    /// The revision the fork was started from
+    #[account(address = map_metadata.fork_source @ ErrorCode::InvalidForkSource)]
+    pub parent_revision: Account<'info, MapRevision>,
+
+    /// The fork being filled
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The fork's data account, written as raw bytes
+    /// CHECK: The seeds and owner checks pin it to the fork, the layout is checked on read
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_data_account: UncheckedAccount<'info>,
+
+    /// The fork's creator or a collaborator, pays rent for the copied objects
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// System program (needed for the rent top-up)
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for allowing or forbidding forks of a map
+#[derive(Accounts)]
+pub struct SetMapForkable<'info> {
+    /// The map to update
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The map creator or an admin collaborator
+    pub user: Signer<'info>,
+}
+
+/// Context for adding, changing and removing map collaborators
+#[derive(Accounts)]
+pub struct ManageMapCollaborators<'info> {
+    /// The map to update
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The map creator, an admin collaborator, or a collaborator leaving
+    pub user: Signer<'info>,
+}
+
+/// Context for offering a map to another wallet
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferMapOwnership<'info> {
+    /// The map to transfer
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The current creator
+    pub user: Signer<'info>,
+}
+
+/// Context for accepting a map ownership offer
+#[derive(Accounts)]
+pub struct AcceptMapOwnership<'info> {
+    /// The map to take over
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The current creator's personal index
+    #[account(
+        mut,
+        seeds = [USER_MAP_INDEX_SEED, map_metadata.creator.as_ref()],
+        bump
+    )]
+    pub from_map_index: Account<'info, UserMapIndex>,
+
+    /// The new owner's personal index
+    /// Uses init_if_needed for owners without maps yet
     #[account(
         init_if_needed,
         payer = user,
-        space = 8 + MapRegistry::INIT_SPACE,
-        seeds = [MAP_REGISTRY_SEED],
+        space = 8 + UserMapIndex::INIT_SPACE,
+        seeds = [USER_MAP_INDEX_SEED, user.key().as_ref()],
+        bump
+    )]
+    pub to_map_index: Account<'info, UserMapIndex>,
+
+    /// The pending owner
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Solana system program
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for creating a map index page
+#[derive(Accounts)]
+#[instruction(kind: MapIndexKind, label: String, page: u32)]
+pub struct InitMapIndexPage<'info> {
+    /// The new index page
+    /// Derived from [MAP_INDEX_SEED, kind, label, page]
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MapIndexPage::INIT_SPACE,
+        seeds = [MAP_INDEX_SEED, &[kind as u8], label.as_bytes(), &page.to_le_bytes()],
+        bump
+    )]
+    pub map_index: Account<'info, MapIndexPage>,
+
+    /// Pays for the page
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Solana system program
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for changing a map's category and tags
+#[derive(Accounts)]
+pub struct UpdateMapLabels<'info> {
+    /// The map to update
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
         bump
     )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The map creator or a collaborator
+    pub user: Signer<'info>,
+}
+
+/// Context for transferring the registry admin role
+#[derive(Accounts)]
+pub struct TransferRegistryAdmin<'info> {
+    /// The global registry PDA
+    #[account(
+        mut,
+        seeds = [MAP_REGISTRY_SEED],
+        bump,
+        has_one = admin @ ErrorCode::NotRegistryAdmin
+    )]
     pub map_registry: Account<'info, MapRegistry>,
+
+    /// The current registry admin
+    pub admin: Signer<'info>,
+}
+
+/// Context for promoting or demoting a default map
+#[derive(Accounts)]
+pub struct SetMapDefault<'info> {
+    /// The global registry PDA, counters are moved between default and user maps
+    #[account(
+        mut,
+        seeds = [MAP_REGISTRY_SEED],
+        bump,
+        has_one = admin @ ErrorCode::NotRegistryAdmin
+    )]
+    pub map_registry: Account<'info, MapRegistry>,
+
+    /// The map to promote or demote
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The registry admin
+    pub admin: Signer<'info>,
+}
+
+/// Context for updating map metadata (name, description)
+#[derive(Accounts)]
+pub struct UpdateMapMetadata<'info> {
+    /// The map's metadata account to update
+    #[account(mut)]
+    pub map_metadata: Account<'info, MapMetadata>,
+    
+    /// The user attempting to update (the creator or a collaborator)
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+/// Context for updating map data
+/// Deserializes the whole map - only usable for small maps, see update_map_data
+#[derive(Accounts)]
+#[instruction(map_data: Vec<MapObject>)]
+pub struct UpdateMapData<'info> {
+    /// The map's metadata (used to verify the editor, spawn counts are updated)
+    /// Seeds constraint ensures we're working with the correct map
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+    
+    /// The map's data account to update
+    /// Uses realloc to resize the account if the new data is a different size
+    /// Each MapObject is 19 bytes: 1 (enum) + 6 (pos) + 6 (rot) + 3 (scale) + 3 (color)
+    #[account(
+        mut,
+        realloc = 8 + 4 + (map_data.len() * 19),
+        realloc::payer = user,
+        realloc::zero = false,
+        seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_data_account: Account<'info, MapData>,
     
-    /// The user paying for the account creation
+    /// The user attempting to update (the creator or a collaborator)
     #[account(mut)]
     pub user: Signer<'info>,
     
-    /// Solana system program (required for creating accounts)
+    /// System program (needed for reallocation)
     pub system_program: Program<'info, System>,
 }
 
-/// Context for creating a new map
+/// Context for chunked writes to a draft map
 #[derive(Accounts)]
-#[instruction(map_id: String, name: String, description: String, is_default: bool, map_data: Vec<MapObject>)]
-pub struct CreateMap<'info> {
-    /// New PDA account to store map metadata (name, creator, timestamps, etc.)
-    /// Derived from [MAP_METADATA_SEED, map_id]
+pub struct WriteMapObjects<'info> {
+    /// The map's metadata (used to verify the creator and draft state, spawn points are recounted)
     #[account(
-        init,
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The map's data account, written as raw bytes
+    /// CHECK: The seeds and owner checks pin it to this map, the layout is checked on read
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_data_account: UncheckedAccount<'info>,
+
+    /// The map creator or a collaborator, pays rent for the added objects
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// System program (needed for the rent top-up)
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the patch instructions (insert, remove, replace, move)
+#[derive(Accounts)]
+pub struct EditMapObjects<'info> {
+    /// The map's metadata (used to verify the creator, updated_at is refreshed)
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The map's data account, edited as raw bytes
+    /// CHECK: The seeds and owner checks pin it to this map, the layout is checked on read
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_data_account: UncheckedAccount<'info>,
+
+    /// The map creator or a collaborator, pays or receives the rent difference
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// System program (needed for the rent top-up)
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for changing a map's publication status
+#[derive(Accounts)]
+pub struct SetMapStatus<'info> {
+    /// The map to update
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The map creator or an admin collaborator
+    pub user: Signer<'info>,
+}
+
+/// Context for rating a map
+#[derive(Accounts)]
+pub struct RateMap<'info> {
+    /// The rated map
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The user's vote, created on the first rating
+    #[account(
+        init_if_needed,
         payer = user,
-        space = 8 + MapMetadata::INIT_SPACE,
-        seeds = [MAP_METADATA_SEED, map_id.as_bytes()],
+        space = 8 + MapRating::INIT_SPACE,
+        seeds = [MAP_RATING_SEED, map_metadata.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub map_rating: Account<'info, MapRating>,
+
+    /// The voter
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Solana system program
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for counting a map play
+#[derive(Accounts)]
+pub struct RecordMapPlay<'info> {
+    /// The played map
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// Matchmaking PDA - only matchmaking can count plays
+    #[account(
+        seeds = [MAP_PLAY_AUTHORITY_SEED],
+        bump,
+        seeds::program = MATCHMAKING_PROGRAM_ID
+    )]
+    pub play_authority: Signer<'info>,
+}
+
+/// Context for finalizing a draft map
+#[derive(Accounts)]
+pub struct FinalizeMap<'info> {
+    /// The map's metadata to finalize
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// The map's data account, read as raw bytes
+    /// CHECK: The seeds and owner checks pin it to this map, the layout is checked on read
+    #[account(
+        owner = crate::ID,
+        seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
+        bump
+    )]
+    pub map_data_account: UncheckedAccount<'info>,
+
+    /// The map creator or an admin collaborator
+    pub user: Signer<'info>,
+}
+
+/// Context for starting a revision publish
+#[derive(Accounts)]
+pub struct PublishMapRevision<'info> {
+    /// The map's metadata, its version is bumped
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
         bump
     )]
     pub map_metadata: Account<'info, MapMetadata>,
-    
-    /// New PDA account to store the actual map data (level layout, objects, etc.)
-    /// Derived from [MAP_DATA_SEED, map_id]
-    /// Space is calculated dynamically based on the size of map_data
-    /// Each MapObject is 19 bytes: 1 (enum) + 6 (pos) + 6 (rot) + 3 (scale) + 3 (color)
+
+    /// The map's data account, read as raw bytes
+    /// CHECK: The seeds and owner checks pin it to this map, the layout is checked on read
     #[account(
-        init,
-        payer = user,
-        space = 8 + 4 + (map_data.len() * 19), // 8 (discriminator) + 4 (vec length) + (objects * 19 bytes each)
-        seeds = [MAP_DATA_SEED, map_id.as_bytes()],
+        owner = crate::ID,
+        seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
         bump
     )]
-    pub map_data_account: Account<'info, MapData>,
-    
-    /// The global registry to update counters
-    #[account(mut)]
-    pub map_registry: Account<'info, MapRegistry>,
-    
-    /// The user's personal index of maps they've created
-    /// Uses init_if_needed for first-time map creators
+    pub map_data_account: UncheckedAccount<'info>,
+
+    /// New snapshot PDA, starts without objects
+    /// Derived from [MAP_REVISION_SEED, map_id, nonce, version]
     #[account(
-        init_if_needed,
+        init,
         payer = user,
-        space = 8 + UserMapIndex::INIT_SPACE,
-        seeds = [USER_MAP_INDEX_SEED, user.key().as_ref()],
+        space = MapRevision::HEADER_SIZE,
+        seeds = [
+            MAP_REVISION_SEED,
+            map_metadata.map_id.as_bytes(),
+            &map_metadata.nonce.to_le_bytes(),
+            &(map_metadata.version + 1).to_le_bytes()
+        ],
         bump
     )]
-    pub user_map_index: Account<'info, UserMapIndex>,
-    
-    /// The map creator and transaction signer
+    pub map_revision: Account<'info, MapRevision>,
+
+    /// The map creator or an admin collaborator, pays for the snapshot
     #[account(mut)]
     pub user: Signer<'info>,
-    
+
     /// Solana system program
     pub system_program: Program<'info, System>,
 }
 
-/// Context for updating map metadata (name, description)
-#[derive(Accounts)]
-pub struct UpdateMapMetadata<'info> {
-    /// The map's metadata account to update
-    #[account(mut)]
-    pub map_metadata: Account<'info, MapMetadata>,
-    
-    /// The user attempting to update (must be the creator)
-    #[account(mut)]
-    pub user: Signer<'info>,
-}
-
-/// Context for updating map data
+/// Context for copying objects into a revision
 #[derive(Accounts)]
-#[instruction(map_data: Vec<MapObject>)]
-pub struct UpdateMapData<'info> {
-    /// The map's metadata (used to verify the creator)
-    /// Seeds constraint ensures we're working with the correct map
+pub struct CopyRevisionObjects<'info> {
+    /// The map being published
     #[account(
+        mut,
         seeds = [MAP_METADATA_SEED, map_metadata.map_id.as_bytes()],
         bump
     )]
     pub map_metadata: Account<'info, MapMetadata>,
-    
-    /// The map's data account to update
-    /// Uses realloc to resize the account if the new data is a different size
-    /// Each MapObject is 19 bytes: 1 (enum) + 6 (pos) + 6 (rot) + 3 (scale) + 3 (color)
+
+    /// The map's data account, read as raw bytes
+    /// CHECK: The seeds and owner checks pin it to this map, the layout is checked on read
     #[account(
-        mut,
-        realloc = 8 + 4 + (map_data.len() * 19),
-        realloc::payer = user,
-        realloc::zero = false,
+        owner = crate::ID,
         seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
         bump
     )]
-    pub map_data_account: Account<'info, MapData>,
-    
-    /// The user attempting to update (must be the creator)
+    pub map_data_account: UncheckedAccount<'info>,
+
+    /// The snapshot being filled
+    #[account(
+        mut,
+        seeds = [
+            MAP_REVISION_SEED,
+            map_metadata.map_id.as_bytes(),
+            &map_metadata.nonce.to_le_bytes(),
+            &map_revision.version.to_le_bytes()
+        ],
+        bump
+    )]
+    pub map_revision: Account<'info, MapRevision>,
+
+    /// The map creator or an admin collaborator, pays rent for the copied objects
     #[account(mut)]
     pub user: Signer<'info>,
-    
-    /// System program (needed for reallocation)
+
+    /// System program (needed for the rent top-up)
     pub system_program: Program<'info, System>,
 }
 
@@ -387,13 +2164,14 @@ pub struct DeleteMap<'info> {
     pub map_metadata: Account<'info, MapMetadata>,
     
     /// The map's data account to close
+    /// CHECK: Closed by hand in the handler, the seeds and owner checks pin it to this map
     #[account(
         mut,
-        close = user,
+        owner = crate::ID,
         seeds = [MAP_DATA_SEED, map_metadata.map_id.as_bytes()],
         bump
     )]
-    pub map_data_account: Account<'info, MapData>,
+    pub map_data_account: UncheckedAccount<'info>,
     
     /// The user's personal index to update
     #[account(
@@ -402,6 +2180,14 @@ pub struct DeleteMap<'info> {
         bump
     )]
     pub user_map_index: Account<'info, UserMapIndex>,
+
+    /// The global index page the map is on
+    #[account(
+        mut,
+        seeds = [MAP_INDEX_SEED, &[MapIndexKind::All as u8], &[], &map_metadata.index_page.to_le_bytes()],
+        bump
+    )]
+    pub map_index: Account<'info, MapIndexPage>,
     
     /// The user deleting the map (must be the creator)
     #[account(mut)]
@@ -423,10 +2209,22 @@ pub struct DeleteMap<'info> {
 #[account]
 #[derive(InitSpace)]
 pub struct MapRegistry {
+    /// Can create default maps and promote/demote existing ones
+    pub admin: Pubkey,
     /// Number of official/default maps
     pub default_maps_count: u32,
     /// Number of user-created maps
     pub user_maps_count: u32,
+    /// Maps ever created, hands out MapMetadata.nonce
+    pub maps_created: u64,
+}
+
+impl MapRegistry {
+    /// Unique per created map, even when a deleted map_id is reused
+    pub fn next_map_nonce(&mut self) -> u64 {
+        self.maps_created += 1;
+        self.maps_created
+    }
 }
 
 /// Metadata about a specific map
@@ -456,6 +2254,291 @@ pub struct MapMetadata {
     
     /// Whether this is an official map or user-created
     pub is_default: bool,
+
+    /// Number of SpawnPointBlue objects in the map data
+    pub blue_spawn_count: u16,
+
+    /// Number of SpawnPointRed objects in the map data
+    pub red_spawn_count: u16,
+
+    /// Publication state, new maps start as Draft
+    pub status: MapStatus,
+
+    /// SHA-256 of the serialized objects (without the Vec length prefix) of the
+    /// published revision, so clients can check their cached copy. Hashed once
+    /// when a revision finishes copying, all zero until the first one does
+    pub content_hash: [u8; 32],
+
+    /// Latest revision started by publish_map_revision, 0 until the map is first published
+    pub version: u32,
+
+    /// Registry-wide creation number, part of the revision seeds
+    pub nonce: u64,
+
+    /// Latest fully copied revision - the one new lobbies are pinned to
+    /// Lags behind `version` while a publish is still copying objects
+    pub published_version: u32,
+
+    /// Number of ratings and the sum of their stars
+    pub rating_count: u32,
+    pub rating_total: u64,
+
+    /// Number of users who like the map
+    pub like_count: u32,
+
+    /// Matches started on the map
+    pub play_count: u64,
+
+    /// Browse category, empty for none
+    #[max_len(16)]
+    pub category: String,
+
+    /// Browse tags, at most MAX_TAGS
+    #[max_len(3, 16)]
+    pub tags: Vec<String>,
+
+    /// Global map index page the map is on
+    pub index_page: u32,
+
+    /// Whether the map is in its category and tag indexes (while Published)
+    pub is_listed: bool,
+
+    /// Category index page the map is listed on, valid while is_listed
+    pub category_page: u32,
+
+    /// Tag index pages the map is listed on, in tag order, valid while is_listed
+    #[max_len(3)]
+    pub tag_pages: Vec<u32>,
+
+    /// Whether other creators may fork the map
+    pub is_forkable: bool,
+
+    /// Number of maps forked from this one
+    pub fork_count: u32,
+
+    /// Map this one was forked from, empty for original maps
+    #[max_len(50)]
+    pub parent_map_id: String,
+
+    /// Parent revision the fork was copied from
+    pub parent_version: u32,
+
+    /// Fork copy progress: the MapRevision being copied, its object count and
+    /// how many objects are in so far
+    pub fork_source: Pubkey,
+    pub fork_object_count: u32,
+    pub fork_copied_count: u32,
+
+    /// Wallets that can edit the map besides the creator, at most MAX_COLLABORATORS
+    #[max_len(10)]
+    pub collaborators: Vec<MapCollaborator>,
+
+    /// Wallet offered the map by transfer_map_ownership, default while no offer is open
+    pub pending_owner: Pubkey,
+}
+
+impl MapMetadata {
+    pub const MAX_TAGS: usize = 3;
+    pub const MAX_COLLABORATORS: usize = 10;
+
+    /// Role of `wallet` on this map, None for strangers (and for the creator)
+    pub fn role_of(&self, wallet: &Pubkey) -> Option<MapRole> {
+        self.collaborators
+            .iter()
+            .find(|c| &c.wallet == wallet)
+            .map(|c| c.role)
+    }
+
+    /// The creator or any collaborator - can change the map's data and metadata
+    pub fn require_editor(&self, wallet: &Pubkey) -> Result<()> {
+        require!(
+            &self.creator == wallet || self.role_of(wallet).is_some(),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    /// The creator or an admin collaborator - can also manage collaborators and publishing
+    pub fn require_admin(&self, wallet: &Pubkey) -> Result<()> {
+        require!(
+            &self.creator == wallet || self.role_of(wallet) == Some(MapRole::Admin),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    /// Validate and store the browse category and tags
+    pub fn set_labels(&mut self, category: String, tags: Vec<String>) -> Result<()> {
+        if !category.is_empty() {
+            validate_label(&category)?;
+        }
+        require!(tags.len() <= Self::MAX_TAGS, ErrorCode::TooManyTags);
+        for (i, tag) in tags.iter().enumerate() {
+            validate_label(tag)?;
+            require!(!tags[..i].contains(tag), ErrorCode::DuplicateMapTag);
+        }
+
+        self.category = category;
+        self.tags = tags;
+        Ok(())
+    }
+
+    /// Past the draft stage - the object list has been sealed at least once
+    pub fn is_finalized(&self) -> bool {
+        self.status != MapStatus::Draft
+    }
+
+    /// Count the spawn points of a whole new object list
+    /// `object_bytes` is the serialized objects without the Vec length prefix
+    pub fn count_spawn_points(&mut self, object_bytes: &[u8]) {
+        (self.blue_spawn_count, self.red_spawn_count) = spawn_point_counts(object_bytes);
+    }
+
+    /// Count the spawn points of objects written into the map
+    pub fn track_added(&mut self, object_bytes: &[u8]) {
+        let (blue, red) = spawn_point_counts(object_bytes);
+        self.blue_spawn_count += blue;
+        self.red_spawn_count += red;
+    }
+
+    /// Uncount the spawn points of objects removed or overwritten
+    pub fn track_removed(&mut self, object_bytes: &[u8]) {
+        let (blue, red) = spawn_point_counts(object_bytes);
+        self.blue_spawn_count = self.blue_spawn_count.saturating_sub(blue);
+        self.red_spawn_count = self.red_spawn_count.saturating_sub(red);
+    }
+
+    /// A fork whose objects are still being copied from the parent
+    pub fn is_fork_pending(&self) -> bool {
+        self.fork_copied_count < self.fork_object_count
+    }
+
+    /// Average stars times 100 (e.g. 425 = 4.25 stars), 0 when unrated
+    pub fn average_rating_x100(&self) -> u64 {
+        if self.rating_count == 0 {
+            return 0;
+        }
+        self.rating_total * 100 / self.rating_count as u64
+    }
+
+    /// Edits may not leave a finalized map unplayable, drafts are unrestricted
+    pub fn require_playable_if_finalized(&self) -> Result<()> {
+        if self.is_finalized() {
+            self.require_playable()?;
+        }
+        Ok(())
+    }
+
+    /// Enough spawn points on each team for a match to be played
+    pub fn require_playable(&self) -> Result<()> {
+        if self.blue_spawn_count < MIN_SPAWN_POINTS_PER_TEAM
+            || self.red_spawn_count < MIN_SPAWN_POINTS_PER_TEAM
+        {
+            msg!(
+                "Map {} has {} blue and {} red spawn points, needs {} per team",
+                self.map_id,
+                self.blue_spawn_count,
+                self.red_spawn_count,
+                MIN_SPAWN_POINTS_PER_TEAM
+            );
+            return err!(ErrorCode::NotEnoughSpawnPoints);
+        }
+        Ok(())
+    }
+}
+
+/// One wallet's vote on a map
+/// PDA derived from [MAP_RATING_SEED, map_metadata, rater]
+#[account]
+#[derive(InitSpace)]
+pub struct MapRating {
+    /// The rated MapMetadata account
+    pub map_metadata: Pubkey,
+
+    /// Wallet that cast the vote
+    pub rater: Pubkey,
+
+    /// Rating from 1 to 5
+    pub stars: u8,
+
+    /// Whether the rater likes the map
+    pub liked: bool,
+
+    /// Unix timestamp of the latest vote
+    pub rated_at: i64,
+}
+
+/// Publication state of a map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub enum MapStatus {
+    /// Still being authored, cannot be played
+    Draft,
+    /// Public, usable by any lobby
+    Published,
+    /// Playable by id in private lobbies, hidden from listings
+    Unlisted,
+    /// Retired by the creator, no new lobbies
+    Delisted,
+}
+
+/// Immutable snapshot of a published map version
+/// PDA derived from [MAP_REVISION_SEED, map_id, nonce, version]
+/// The nonce keeps a recreated map_id from colliding with a deleted map's revisions
+/// The serialized objects follow the fixed fields, `copied_count` of them so far
+#[account]
+#[derive(InitSpace)]
+pub struct MapRevision {
+    /// The MapMetadata account this is a revision of
+    pub map_metadata: Pubkey,
+
+    /// Version number, starting at 1
+    pub version: u32,
+
+    /// Hash of the objects, set once the copy completes
+    pub content_hash: [u8; 32],
+
+    /// Spawn point counts of this revision
+    pub blue_spawn_count: u16,
+    pub red_spawn_count: u16,
+
+    /// Objects in the revision, and how many have been copied in
+    pub object_count: u32,
+    pub copied_count: u32,
+
+    /// Unix timestamp when publishing started
+    pub published_at: i64,
+}
+
+impl MapRevision {
+    /// Discriminator + fixed fields, the objects start here
+    pub const HEADER_SIZE: usize = 8 + Self::INIT_SPACE;
+
+    /// Keeps each copy under the 10KB realloc limit per instruction
+    pub const MAX_OBJECTS_PER_COPY: usize = 10_240 / MapData::OBJECT_SIZE;
+
+    /// All objects are in - only complete revisions can be played
+    pub fn is_complete(&self) -> bool {
+        self.copied_count == self.object_count
+    }
+
+    /// Copy `object_count` objects from the start
+    fn expect_objects(&mut self, object_count: usize) {
+        self.content_hash = [0; 32];
+        self.object_count = object_count as u32;
+        self.copied_count = 0;
+    }
+
+    /// Record the hash and spawn counts of a complete copy that matches the map
+    fn seal(&mut self, content_hash: [u8; 32], map_metadata: &MapMetadata) {
+        self.content_hash = content_hash;
+        self.blue_spawn_count = map_metadata.blue_spawn_count;
+        self.red_spawn_count = map_metadata.red_spawn_count;
+    }
+
+    /// Both teams have somewhere to spawn
+    pub fn has_team_spawns(&self) -> bool {
+        self.blue_spawn_count > 0 && self.red_spawn_count > 0
+    }
 }
 
 /// Model types available for map objects
@@ -502,6 +2585,47 @@ pub struct MapObject {
     pub color_b: u8,
 }
 
+/// New value for the object at `index`, used by replace_map_objects
+#[derive(Debug, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct MapObjectPatch {
+    pub index: u32,
+    pub object: MapObject,
+}
+
+impl MapObject {
+    /// Largest rotation value, in degrees
+    pub const MAX_ROTATION: u16 = 360;
+
+    /// Check the field ranges documented above, logging the offending object index
+    pub fn validate(&self, index: usize) -> Result<()> {
+        if self.scale_x == 0 || self.scale_y == 0 || self.scale_z == 0 {
+            msg!(
+                "Map object {}: scale ({}, {}, {}) must be at least 1 (0.1) on every axis",
+                index,
+                self.scale_x,
+                self.scale_y,
+                self.scale_z
+            );
+            return err!(ErrorCode::InvalidObjectScale);
+        }
+        if self.rot_x > Self::MAX_ROTATION
+            || self.rot_y > Self::MAX_ROTATION
+            || self.rot_z > Self::MAX_ROTATION
+        {
+            msg!(
+                "Map object {}: rotation ({}, {}, {}) must be 0-{} degrees",
+                index,
+                self.rot_x,
+                self.rot_y,
+                self.rot_z,
+                Self::MAX_ROTATION
+            );
+            return err!(ErrorCode::InvalidObjectRotation);
+        }
+        Ok(())
+    }
+}
+
 /// The actual map data (level layout, tiles, objects, etc.)
 #[account]
 pub struct MapData {
@@ -510,9 +2634,109 @@ pub struct MapData {
 }
 
 impl MapData {
-    /// Maximum size for map data (10KB)
-    /// Adjust this based on your needs and Solana account size limits
-    pub const MAX_SIZE: usize = 10_000;
+    /// Maximum number of objects in a map
+    /// 10,000 objects of 19 bytes is ~190KB of account data, built up in chunks
+    pub const MAX_OBJECTS: usize = 10_000;
+
+    /// Discriminator + Vec length prefix
+    pub const HEADER_SIZE: usize = 8 + 4;
+
+    /// Each MapObject is 19 bytes: 1 (enum) + 6 (pos) + 6 (rot) + 3 (scale) + 3 (color)
+    pub const OBJECT_SIZE: usize = 19;
+
+    /// Number of objects in raw MapData account data
+    pub fn object_count(data: &[u8]) -> Result<usize> {
+        require!(
+            data.len() >= Self::HEADER_SIZE && data.starts_with(Self::DISCRIMINATOR),
+            ErrorCode::InvalidMapData
+        );
+        let count = u32::from_le_bytes(data[8..Self::HEADER_SIZE].try_into().unwrap()) as usize;
+        require!(
+            data.len() >= Self::HEADER_SIZE + count * Self::OBJECT_SIZE,
+            ErrorCode::InvalidMapData
+        );
+        Ok(count)
+    }
+}
+
+/// What a collaborator may do on a map
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum MapRole {
+    /// Edit the map's data and metadata
+    Editor,
+    /// Editor rights, plus managing collaborators, finalizing and publishing
+    Admin,
+}
+
+/// A wallet allowed to work on a map besides its creator
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MapCollaborator {
+    pub wallet: Pubkey,
+    pub role: MapRole,
+}
+
+/// Which index a MapIndexPage belongs to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum MapIndexKind {
+    /// Every map in the registry
+    All,
+    /// Published maps in one category
+    Category,
+    /// Published maps with one tag
+    Tag,
+}
+
+/// One page of a map index, lets map browsers list maps without getProgramAccounts
+/// PDA derived from [MAP_INDEX_SEED, kind, label, page]
+#[account]
+#[derive(InitSpace)]
+pub struct MapIndexPage {
+    pub kind: MapIndexKind,
+
+    /// The category or tag, empty for the global index
+    #[max_len(16)]
+    pub label: String,
+
+    pub page: u32,
+
+    /// Map IDs on this page, at most MAX_MAPS
+    #[max_len(32, 50)]
+    pub map_ids: Vec<String>,
+}
+
+impl MapIndexPage {
+    pub const MAX_MAPS: usize = 32;
+    pub const MAX_LABEL_LEN: usize = 16;
+
+    /// The PDA of this page
+    pub fn address(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                MAP_INDEX_SEED,
+                &[self.kind as u8],
+                self.label.as_bytes(),
+                &self.page.to_le_bytes(),
+            ],
+            &crate::ID,
+        )
+        .0
+    }
+
+    pub fn insert(&mut self, map_id: String) -> Result<()> {
+        require!(self.map_ids.len() < Self::MAX_MAPS, ErrorCode::MapIndexPageFull);
+        self.map_ids.push(map_id);
+        Ok(())
+    }
+
+    /// Drop the map from this page, returns true if it was listed here
+    pub fn remove(&mut self, map_id: &str) -> bool {
+        if let Some(pos) = self.map_ids.iter().position(|id| id == map_id) {
+            self.map_ids.remove(pos);
+            true
+        } else {
+            false
+        }
+    }
 }
 
 /// Personal index for each user to track their created maps
@@ -537,12 +2761,105 @@ pub struct UserMapIndex {
 
 #[error_code]
 pub enum ErrorCode {
-    #[msg("Unauthorized: You are not the creator of this map")]
+    #[msg("Unauthorized: You are not the creator or a collaborator of this map")]
     Unauthorized,
     
     #[msg("User has reached the maximum number of maps (100)")]
     UserMapLimitReached,
     
-    #[msg("Map data exceeds maximum allowed size (10KB)")]
+    #[msg("Map exceeds the maximum of 10,000 objects")]
     MapDataTooLarge,
+
+    #[msg("Unauthorized: Only the registry admin can manage default maps")]
+    NotRegistryAdmin,
+
+    #[msg("Default maps cannot be deleted - the admin must demote them first")]
+    DefaultMapProtected,
+
+    #[msg("Map is already finalized")]
+    MapFinalized,
+
+    #[msg("Write offset is past the end of the map")]
+    InvalidWriteOffset,
+
+    #[msg("Map data account is malformed")]
+    InvalidMapData,
+
+    #[msg("Map has no objects")]
+    EmptyMap,
+
+    #[msg("Object index is out of bounds")]
+    ObjectIndexOutOfBounds,
+
+    #[msg("Map must be finalized before it can be published")]
+    MapNotFinalized,
+
+    #[msg("Map revision has already been fully copied")]
+    RevisionComplete,
+
+    #[msg("Maps cannot be moved back to draft")]
+    InvalidStatusTransition,
+
+    #[msg("Map object scale must be at least 0.1 on every axis")]
+    InvalidObjectScale,
+
+    #[msg("Map object rotation must be between 0 and 360 degrees")]
+    InvalidObjectRotation,
+
+    #[msg("Map does not have enough spawn points for both teams")]
+    NotEnoughSpawnPoints,
+
+    #[msg("You cannot rate your own map")]
+    CannotRateOwnMap,
+
+    #[msg("Rating must be between 1 and 5 stars")]
+    InvalidRating,
+
+    #[msg("Categories and tags are 1-16 characters of a-z, 0-9 and '-'")]
+    InvalidMapLabel,
+
+    #[msg("Maps can have at most 3 tags")]
+    TooManyTags,
+
+    #[msg("Map tags must be unique")]
+    DuplicateMapTag,
+
+    #[msg("Map index page is full - use or create the next page")]
+    MapIndexPageFull,
+
+    #[msg("Map index page does not match the map's category, tag or page")]
+    InvalidMapIndexPage,
+
+    #[msg("One index page is required for the category and for each tag")]
+    MissingMapIndexPage,
+
+    #[msg("Map is listed - unpublish it before changing its category or tags")]
+    MapListed,
+
+    #[msg("The map's creator does not allow forks")]
+    MapNotForkable,
+
+    #[msg("Fork is still copying objects from its parent")]
+    ForkCopyPending,
+
+    #[msg("Fork has already been fully copied")]
+    ForkComplete,
+
+    #[msg("Maps can have at most 10 collaborators")]
+    TooManyCollaborators,
+
+    #[msg("Wallet is the map's creator or not one of its collaborators")]
+    InvalidCollaborator,
+
+    #[msg("Only the program's upgrade authority can initialize the registry")]
+    NotUpgradeAuthority,
+
+    #[msg("Map has no published revision")]
+    NoPublishedRevision,
+
+    #[msg("Revision is not the one this fork was started from")]
+    InvalidForkSource,
+
+    #[msg("No ownership offer is open for this wallet")]
+    NoPendingTransfer,
 }
\ No newline at end of file
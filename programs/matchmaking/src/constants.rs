@@ -6,8 +6,57 @@ pub const SEED: &str = "anchor";
 // PDA Seeds
 pub const PLAYER_SEED: &str = "player";
 pub const GAME_SEED: &str = "game";
+pub const LOBBY_DIRECTORY_SEED: &str = "lobby_directory";
+pub const QUEUE_SEED: &str = "match_queue";
+pub const CONFIG_SEED: &str = "config";
+pub const LEVEL_CURVE_SEED: &str = "level_curve";
+pub const MATCH_RECORD_SEED: &str = "match_record";
+pub const MATCH_HISTORY_SEED: &str = "match_history";
+pub const SEASON_SEED: &str = "season";
+pub const USERNAME_SEED: &str = "username";
+
+// Game program PDA that signs match result reports and idle kicks via CPI
+pub const SETTLEMENT_AUTHORITY_SEED: &str = "settlement";
+pub const GAME_PROGRAM_ID: Pubkey = anchor_lang::solana_program::pubkey!("BxRTWoDrsLCguuGVMgAEVJgfRPpmdiGzjFvXYhTUmMLU");
+
+// Usernames
+pub const RENAME_COOLDOWN: i64 = 7 * 24 * 60 * 60; // one rename per week
 
 // Game Constraints
 pub const MAX_PLAYERS_PER_TEAM: u8 = 5;
 pub const MAX_TOTAL_PLAYERS: u8 = 10;
 pub const MIN_PLAYERS_TO_START: u8 = 2;
+pub const RECONNECT_GRACE_PERIOD: i64 = 60; // seconds a player's slot is held after leaving mid-match
+pub const DEFAULT_IDLE_TIMEOUT: i64 = 30; // seconds without input before a player can be kicked
+pub const MIN_IDLE_TIMEOUT: i64 = 10;
+pub const MAX_PAUSE_DURATION: i64 = 600; // seconds a match can stay paused before anyone can end it
+
+// Lobby Directory
+pub const MAX_LOBBIES_PER_PAGE: usize = 20;
+pub const DEFAULT_LOBBY_IDLE_TIMEOUT: i64 = 1800; // 30 minutes
+pub const MIN_LOBBY_IDLE_TIMEOUT: i64 = 60;
+
+// Matchmaking Queue
+pub const MAX_QUEUE_ENTRIES: usize = 50;
+pub const QUEUE_ENTRY_TIMEOUT: i64 = 120; // seconds before a queue entry goes stale
+
+// Match Settlement
+pub const SETTLEMENT_TIMEOUT: i64 = 120; // seconds after match end to wait for missing reports
+pub const MAX_MATCH_HISTORY: usize = 50;
+
+// Skill Rating (Elo)
+pub const DEFAULT_RATING: u32 = 1000;
+pub const RATING_K_FACTOR: f64 = 32.0;
+pub const PROVISIONAL_K_FACTOR: f64 = 48.0; // faster movement while the rating settles
+pub const PROVISIONAL_MATCHES: u32 = 10;
+
+// Experience & Leveling
+pub const MAX_LEVELS: usize = 100;
+pub const DEFAULT_LEVEL_COUNT: u64 = 50;
+pub const DEFAULT_XP_PER_KILL: u32 = 10;
+pub const DEFAULT_XP_PER_SCORE_POINT: u32 = 1;
+pub const DEFAULT_XP_PER_WIN: u32 = 100;
+pub const DEFAULT_XP_PER_MATCH: u32 = 50;
+
+// Seasons & Leaderboards
+pub const LEADERBOARD_SIZE: usize = 10;
@@ -10,12 +10,42 @@ pub enum InitPlayerError {
     InvalidUsernameLength,
 }
 
+#[error_code]
+pub enum RenameError {
+    #[msg("Player not registered")]
+    PlayerNotRegistered,
+    #[msg("Username can only be changed once per cooldown period")]
+    RenameCooldownActive,
+    #[msg("New username is the same as the current one")]
+    SameUsername,
+    #[msg("Username record belongs to another player")]
+    NotUsernameOwner,
+    #[msg("A record for the new username is required")]
+    UsernameRecordRequired,
+}
+
+#[error_code]
+pub enum MapError {
+    #[msg("Map is a draft or has been delisted")]
+    MapNotPlayable,
+    #[msg("Unlisted maps can only be used in private lobbies")]
+    MapNotPublic,
+    #[msg("Map revision is still being published")]
+    RevisionIncomplete,
+    #[msg("Map needs at least one spawn point for each team")]
+    MissingTeamSpawns,
+    #[msg("Map account is not the lobby's map")]
+    WrongMap,
+}
+
 #[error_code]
 pub enum InitGameError {
     #[msg("Player not registered")]
     PlayerNotRegistered,
     #[msg("Player already in a game")]
     PlayerAlreadyInGame,
+    #[msg("Player is waiting in the matchmaking queue")]
+    PlayerInQueue,
 }
 
 #[error_code]
@@ -32,6 +62,8 @@ pub enum JoinGameError {
     TeamFull,
     #[msg("Invalid game state")]
     InvalidGameState,
+    #[msg("Player is waiting in the matchmaking queue")]
+    PlayerInQueue,
 }
 
 #[error_code]
@@ -42,6 +74,10 @@ pub enum LeaveGameError {
     PlayerNotInThisGame,
     #[msg("Arithmetic overflow")]
     ArithmeticOverflow,
+    #[msg("The next owner's Player account is required when the owner leaves")]
+    NextOwnerRequired,
+    #[msg("Next owner must be the first remaining player")]
+    WrongNextOwner,
 }
 
 #[error_code]
@@ -69,3 +105,163 @@ pub enum SetReadyStateError {
     #[msg("Arithmetic overflow")]
     ArithmeticOverflow,
 }
+
+#[error_code]
+pub enum LobbyDirectoryError {
+    #[msg("Lobby directory page is full")]
+    DirectoryPageFull,
+    #[msg("Lobby directory page is required for listed lobbies")]
+    DirectoryPageRequired,
+    #[msg("Lobby directory page is not the one the lobby is listed on")]
+    WrongDirectoryPage,
+}
+
+#[error_code]
+pub enum QueueError {
+    #[msg("Player not registered")]
+    PlayerNotRegistered,
+    #[msg("Player already in a game")]
+    PlayerAlreadyInGame,
+    #[msg("Player is already waiting in a queue")]
+    PlayerAlreadyQueued,
+    #[msg("Player is not waiting in this queue")]
+    PlayerNotQueued,
+    #[msg("Queue is full")]
+    QueueFull,
+    #[msg("Not enough queued players to start a match")]
+    NotEnoughPlayers,
+    #[msg("Too many players for one match")]
+    TooManyPlayers,
+    #[msg("Game is not a waiting public lobby")]
+    InvalidGameState,
+}
+
+#[error_code]
+pub enum SettlementError {
+    #[msg("Game is not active")]
+    GameNotActive,
+    #[msg("Match duration has not elapsed - only the lobby owner can end early")]
+    MatchStillRunning,
+    #[msg("Game has not ended yet")]
+    GameNotEnded,
+    #[msg("Match has already been settled")]
+    AlreadySettled,
+    #[msg("Player is not on a team in this game")]
+    PlayerNotInGame,
+    #[msg("Still waiting for player results to be reported")]
+    ResultsPending,
+    #[msg("Result for this player has already been recorded")]
+    ResultAlreadyRecorded,
+}
+
+#[error_code]
+pub enum BalanceTeamsError {
+    #[msg("Only lobby owner can balance teams")]
+    NotLobbyOwner,
+    #[msg("Game has already started")]
+    GameAlreadyStarted,
+}
+
+#[error_code]
+pub enum RosterError {
+    #[msg("Every rostered player account must be passed exactly once")]
+    RosterMismatch,
+}
+
+#[error_code]
+pub enum ConfigError {
+    #[msg("Only the matchmaking admin can do this")]
+    Unauthorized,
+    #[msg("Lobby idle timeout is too short")]
+    IdleTimeoutTooShort,
+    #[msg("Only the program's upgrade authority can initialize the config")]
+    NotUpgradeAuthority,
+}
+
+#[error_code]
+pub enum LevelCurveError {
+    #[msg("Level thresholds must be strictly increasing")]
+    InvalidThresholds,
+    #[msg("Level curve has too many levels")]
+    TooManyLevels,
+}
+
+#[error_code]
+pub enum SeasonError {
+    #[msg("A season is already active")]
+    SeasonAlreadyActive,
+    #[msg("Season is not active")]
+    SeasonNotActive,
+    #[msg("The active season account is required")]
+    SeasonRequired,
+}
+
+#[error_code]
+pub enum PauseError {
+    #[msg("Only lobby owner can pause or resume the match")]
+    NotLobbyOwner,
+    #[msg("Game is not active")]
+    GameNotActive,
+    #[msg("Game is not paused")]
+    GameNotPaused,
+}
+
+#[error_code]
+pub enum CleanupError {
+    #[msg("Lobby is still active")]
+    LobbyNotStale,
+    #[msg("Player not in this specific game")]
+    PlayerNotInThisGame,
+    #[msg("Match is still in progress - use leave_game")]
+    MatchInProgress,
+    #[msg("Rent must go back to the lobby owner")]
+    WrongCreator,
+}
+
+#[error_code]
+pub enum RejoinError {
+    #[msg("Player not registered")]
+    PlayerNotRegistered,
+    #[msg("Player already in a game")]
+    PlayerAlreadyInGame,
+    #[msg("Match is not in progress")]
+    MatchNotInProgress,
+    #[msg("No reserved slot for this player")]
+    NoReservedSlot,
+    #[msg("Reconnect grace period has expired")]
+    ReservationExpired,
+}
+
+#[error_code]
+pub enum IdleError {
+    #[msg("Only lobby owner can change the idle timeout")]
+    NotLobbyOwner,
+    #[msg("Idle timeout can only be changed before the match starts")]
+    GameAlreadyStarted,
+    #[msg("Idle timeout is too short")]
+    IdleTimeoutTooShort,
+    #[msg("Match is not in progress")]
+    MatchNotInProgress,
+    #[msg("Player is not on a team in this game")]
+    PlayerNotInGame,
+}
+
+#[error_code]
+pub enum ClosePlayerError {
+    #[msg("Player is still in a game")]
+    PlayerInGame,
+    #[msg("Player is waiting in the matchmaking queue")]
+    PlayerInQueue,
+    #[msg("Username record belongs to another player")]
+    NotUsernameOwner,
+}
+
+#[error_code]
+pub enum LobbySettingsError {
+    #[msg("Only lobby owner can change lobby settings")]
+    NotLobbyOwner,
+    #[msg("Lobby settings can only be changed before the match starts")]
+    GameAlreadyStarted,
+    #[msg("Lobby name must be between 1 and 32 characters")]
+    InvalidLobbyName,
+}
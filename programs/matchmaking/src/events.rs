@@ -0,0 +1,10 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PlayerLeveledUp {
+    pub player: Pubkey,
+    pub authority: Pubkey,
+    pub old_level: u32,
+    pub new_level: u32,
+    pub xp: u64,
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::error::BalanceTeamsError;
+use crate::state::Game;
+
+/// Re-split the lobby into teams of similar average rating
+/// Remaining accounts: every rostered Player PDA (writable)
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, BalanceTeams<'info>>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+
+    require!(game.game_state == 0, BalanceTeamsError::GameAlreadyStarted);
+    require!(
+        ctx.accounts.authority.key() == game.created_by,
+        BalanceTeamsError::NotLobbyOwner
+    );
+
+    let mut players = game.load_roster(ctx.remaining_accounts)?;
+
+    // Greedy partition - strongest players first, each to the weaker team that still has room
+    players.sort_by_key(|p| std::cmp::Reverse(p.rating));
+    let team_size = players.len().div_ceil(2);
+
+    game.team_a_players = Vec::new();
+    game.team_b_players = Vec::new();
+    game.current_players_team_a = 0;
+    game.current_players_team_b = 0;
+
+    let mut team_a_rating: u64 = 0;
+    let mut team_b_rating: u64 = 0;
+    for player in players.iter_mut() {
+        let team_a_open = game.team_a_players.len() < team_size;
+        let team_b_open = game.team_b_players.len() < team_size;
+        let team = if team_a_open && (!team_b_open || team_a_rating <= team_b_rating) { 1 } else { 2 };
+
+        game.add_player_to_team(player.key(), team)?;
+        if team == 1 {
+            team_a_rating += player.rating as u64;
+        } else {
+            team_b_rating += player.rating as u64;
+        }
+
+        player.team = team;
+        player.exit(&crate::ID)?;
+    }
+
+    msg!(
+        "Balanced game {} - Team A rating {}, Team B rating {}",
+        game.key(),
+        team_a_rating,
+        team_b_rating
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BalanceTeams<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    pub authority: Signer<'info>,
+}
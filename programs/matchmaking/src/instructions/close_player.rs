@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::error::ClosePlayerError;
+use crate::state::{normalize_username, Player, UsernameRecord};
+use crate::constants::{PLAYER_SEED, USERNAME_SEED};
+
+/// Delete a player profile and refund its rent to the authority
+/// Also releases the player's username so someone else can claim it
+pub fn handler(ctx: Context<ClosePlayer>) -> Result<()> {
+    let player = &ctx.accounts.player;
+
+    require!(player.current_game.is_none(), ClosePlayerError::PlayerInGame);
+    require!(
+        !player.is_queued(Clock::get()?.unix_timestamp),
+        ClosePlayerError::PlayerInQueue
+    );
+
+    msg!("Player {} ({}) closed", player.key(), player.username);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePlayer<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PLAYER_SEED.as_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub player: Account<'info, Player>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [USERNAME_SEED.as_bytes(), &normalize_username(&player.username)],
+        bump,
+        constraint = username_record.owner == authority.key() @ ClosePlayerError::NotUsernameOwner
+    )]
+    pub username_record: Account<'info, UsernameRecord>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::error::{ConfigError, SeasonError};
+use crate::state::{MatchmakingConfig, Season};
+use crate::constants::{CONFIG_SEED, SEASON_SEED};
+
+/// Close the active season - admin only
+/// The Season account keeps the final standings as the archive
+pub fn handler(ctx: Context<CloseSeason>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let season = &mut ctx.accounts.season;
+
+    require!(season.is_active, SeasonError::SeasonNotActive);
+
+    season.is_active = false;
+    season.ended_at = Some(Clock::get()?.unix_timestamp);
+    config.season_active = false;
+
+    msg!(
+        "Season {} closed - {} players ranked by rating",
+        season.season_id,
+        season.top_rating.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseSeason<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump,
+        has_one = admin @ ConfigError::Unauthorized
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    #[account(
+        mut,
+        seeds = [SEASON_SEED.as_bytes(), &config.current_season.to_le_bytes()],
+        bump
+    )]
+    pub season: Account<'info, Season>,
+
+    pub admin: Signer<'info>,
+}
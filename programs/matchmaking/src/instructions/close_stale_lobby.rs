@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::error::{CleanupError, LobbyDirectoryError};
+use crate::state::{Game, LobbyDirectory, MatchmakingConfig};
+use crate::constants::{CONFIG_SEED, LOBBY_DIRECTORY_SEED};
+
+/// Close an abandoned lobby or a long-settled match - permissionless
+/// The rent goes back to the lobby owner's wallet; players still pointing at
+/// the game can release themselves with force_leave_stale_game
+pub fn handler(ctx: Context<CloseStaleLobby>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        game.is_stale(now, ctx.accounts.config.lobby_idle_timeout),
+        CleanupError::LobbyNotStale
+    );
+
+    if game.is_listed {
+        let lobby_directory = ctx.accounts.lobby_directory.as_mut()
+            .ok_or(LobbyDirectoryError::DirectoryPageRequired)?;
+        lobby_directory.remove(&game.key());
+        game.is_listed = false;
+    }
+
+    msg!(
+        "Closed stale game {} (state {}, idle {}s)",
+        game.key(),
+        game.game_state,
+        now.saturating_sub(game.last_activity)
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseStaleLobby<'info> {
+    #[account(mut, close = creator)]
+    pub game: Account<'info, Game>,
+
+    /// Wallet of the lobby owner, receives the rent
+    #[account(
+        mut,
+        address = game.created_by @ CleanupError::WrongCreator
+    )]
+    pub creator: SystemAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    /// Directory page the lobby is listed on, required while the lobby is listed
+    #[account(
+        mut,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &game.lobby_page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Option<Account<'info, LobbyDirectory>>,
+}
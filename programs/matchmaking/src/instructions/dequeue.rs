@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::error::QueueError;
+use crate::state::{MatchQueue, Player};
+use crate::constants::PLAYER_SEED;
+
+/// Leave the matchmaking queue
+pub fn handler(ctx: Context<Dequeue>) -> Result<()> {
+    let player = &mut ctx.accounts.player;
+    let queue = &mut ctx.accounts.queue;
+
+    // The entry may already have been pruned as stale, release the player either way
+    queue.take(&player.key());
+
+    player.current_queue = None;
+    player.queued_at = 0;
+
+    msg!("Player {} left the queue", player.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Dequeue<'info> {
+    #[account(
+        mut,
+        constraint = player.current_queue == Some(queue.key()) @ QueueError::PlayerNotQueued
+    )]
+    pub queue: Account<'info, MatchQueue>,
+
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub player: Account<'info, Player>,
+
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::error::SettlementError;
+use crate::state::Game;
+
+/// End an active match so results can be reported and settled
+/// The lobby owner can end at any time, anyone can once match_duration has elapsed
+/// or the match has been paused for longer than MAX_PAUSE_DURATION
+pub fn handler(ctx: Context<EndGame>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let clock = Clock::get()?;
+
+    require!(game.game_state == 1 || game.is_paused(), SettlementError::GameNotActive);
+
+    // Paused time does not count toward the match duration, but an abandoned
+    // pause must not hold the players forever
+    let is_lobby_owner = ctx.accounts.authority.key() == game.created_by;
+    let elapsed = game.active_elapsed(clock.unix_timestamp);
+    require!(
+        is_lobby_owner || game.is_overdue(clock.unix_timestamp),
+        SettlementError::MatchStillRunning
+    );
+
+    game.finish(clock.unix_timestamp);
+
+    msg!("Game {} ended after {} seconds", game.key(), elapsed);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct EndGame<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    pub authority: Signer<'info>,
+}
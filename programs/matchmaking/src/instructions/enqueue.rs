@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::error::QueueError;
+use crate::state::{MatchQueue, Player};
+use crate::constants::{PLAYER_SEED, QUEUE_SEED};
+
+/// Put the player in the matchmaking queue for a mode and region
+pub fn handler(ctx: Context<Enqueue>, _mode: u8, _region: u8) -> Result<()> {
+    let player = &mut ctx.accounts.player;
+    let queue = &mut ctx.accounts.queue;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(player.has_logged_in, QueueError::PlayerNotRegistered);
+    require!(player.current_game.is_none(), QueueError::PlayerAlreadyInGame);
+    require!(!player.is_queued(now), QueueError::PlayerAlreadyQueued);
+
+    // Make room by dropping timed out entries, including an old one for this player
+    queue.prune_stale(now);
+    queue.take(&player.key());
+    queue.push(player.key(), now)?;
+
+    player.current_queue = Some(queue.key());
+    player.queued_at = now;
+
+    msg!(
+        "Player {} queued for mode {} region {} ({} waiting)",
+        player.key(),
+        queue.mode,
+        queue.region,
+        queue.entries.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(mode: u8, region: u8)]
+pub struct Enqueue<'info> {
+    #[account(
+        mut,
+        seeds = [QUEUE_SEED.as_bytes(), &[mode], &[region]],
+        bump
+    )]
+    pub queue: Account<'info, MatchQueue>,
+
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub player: Account<'info, Player>,
+
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use crate::error::QueueError;
+use crate::state::{Game, LobbyDirectory, MatchQueue};
+use crate::constants::LOBBY_DIRECTORY_SEED;
+use crate::instructions::match_queue::seat_queued_players;
+
+/// Permissionless crank - backfills a waiting public lobby with queued players
+/// Remaining accounts: the Player PDAs to seat (writable), all waiting in this queue
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, FillFromQueue<'info>>) -> Result<()> {
+    let queue = &mut ctx.accounts.queue;
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        game.game_state == 0 && !game.is_private && game.match_type == queue.mode,
+        QueueError::InvalidGameState
+    );
+    require!(!ctx.remaining_accounts.is_empty(), QueueError::NotEnoughPlayers);
+
+    queue.prune_stale(now);
+    seat_queued_players(queue, game, ctx.remaining_accounts)?;
+    game.last_activity = now;
+
+    // Refresh the player counts shown in the lobby directory, full lobbies drop out
+    let game_key = game.key();
+    game.sync_listing(game_key, ctx.accounts.lobby_directory.as_deref_mut())?;
+
+    msg!(
+        "Filled game {} from queue (team A: {}, team B: {})",
+        game.key(),
+        game.current_players_team_a,
+        game.current_players_team_b
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FillFromQueue<'info> {
+    #[account(mut)]
+    pub queue: Account<'info, MatchQueue>,
+
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    /// Directory page the lobby is listed on, required while the lobby is listed
+    #[account(
+        mut,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &game.lobby_page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Option<Account<'info, LobbyDirectory>>,
+
+    pub payer: Signer<'info>,
+}
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::error::{CleanupError, LobbyDirectoryError};
+use crate::state::{Game, LobbyDirectory, MatchmakingConfig, Player};
+use crate::constants::{CONFIG_SEED, LOBBY_DIRECTORY_SEED, PLAYER_SEED};
+
+/// Release a player whose game was closed, has ended or expired as a lobby
+/// Lets players stuck on an abandoned game create or join a new one
+/// A match that has overrun its duration or sat paused past MAX_PAUSE_DURATION
+/// is ended first, so it can be settled as usual
+/// Leaving a waiting lobby works like leave_game: the directory row is synced,
+/// ownership is handed on, and an emptied lobby is closed
+pub fn handler(ctx: Context<ForceLeaveStaleGame>) -> Result<()> {
+    let player = &mut ctx.accounts.player;
+    let game_info = ctx.accounts.game.to_account_info();
+    let now = Clock::get()?.unix_timestamp;
+
+    // A closed game no longer belongs to this program (or has no data left)
+    let game_closed = game_info.owner != &crate::ID || game_info.data_is_empty();
+
+    if !game_closed {
+        let mut game = Game::try_deserialize(&mut &game_info.try_borrow_data()?[..])?;
+
+        match game.game_state {
+            // Waiting lobby - only once nobody has touched it for the idle timeout
+            0 => {
+                require!(
+                    now.saturating_sub(game.last_activity) >= ctx.accounts.config.lobby_idle_timeout,
+                    CleanupError::LobbyNotStale
+                );
+                game.remove_player(&player.key());
+                if player.is_ready {
+                    game.ready_players = game.ready_players.saturating_sub(1);
+                }
+
+                // The page can't be pinned by seeds as the game is unchecked
+                if let Some(lobby_directory) = &ctx.accounts.lobby_directory {
+                    let (expected, _) = Pubkey::find_program_address(
+                        &[LOBBY_DIRECTORY_SEED.as_bytes(), &game.lobby_page.to_le_bytes()],
+                        &crate::ID,
+                    );
+                    require_keys_eq!(
+                        lobby_directory.key(),
+                        expected,
+                        LobbyDirectoryError::WrongDirectoryPage
+                    );
+                }
+                let owner_left = game.created_by == ctx.accounts.authority.key();
+                let close_game = game.after_player_left(
+                    game_info.key(),
+                    owner_left,
+                    ctx.accounts.lobby_directory.as_deref_mut(),
+                    ctx.accounts.next_owner.as_ref(),
+                )?;
+
+                if close_game {
+                    let authority_info = ctx.accounts.authority.to_account_info();
+                    **authority_info.try_borrow_mut_lamports()? += game_info.lamports();
+                    **game_info.try_borrow_mut_lamports()? = 0;
+                    game_info.assign(&system_program::ID);
+                    game_info.realloc(0, false)?;
+                    msg!("Game closed - no players remaining");
+                } else {
+                    game.try_serialize(&mut &mut game_info.try_borrow_mut_data()?[..])?;
+                }
+            }
+            // Ended - settlement skips the release for players that already left
+            2 => {}
+            // Running or paused, but nobody ended it in time
+            1 | 3 => {
+                require!(game.is_overdue(now), CleanupError::MatchInProgress);
+                game.finish(now);
+                game.try_serialize(&mut &mut game_info.try_borrow_mut_data()?[..])?;
+                msg!("Overdue game {} ended", game_info.key());
+            }
+            _ => return err!(CleanupError::MatchInProgress),
+        }
+    }
+
+    player.current_game = None;
+    player.team = 0;
+    player.is_alive = false;
+    player.is_ready = false;
+    player.is_spectator = false;
+
+    msg!("Player {} released from stale game {}", player.key(), game_info.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ForceLeaveStaleGame<'info> {
+    /// CHECK: May already be closed - the handler only deserializes it when still owned by this program
+    #[account(mut)]
+    pub game: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_bytes(), authority.key().as_ref()],
+        bump,
+        constraint = player.current_game == Some(game.key()) @ CleanupError::PlayerNotInThisGame
+    )]
+    pub player: Account<'info, Player>,
+
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    /// Directory page the lobby is listed on, required while the lobby is listed
+    /// or when a full public lobby frees a slot and is listed again
+    /// Checked against the game's lobby_page in the handler
+    #[account(mut)]
+    pub lobby_directory: Option<Account<'info, LobbyDirectory>>,
+
+    /// Player account of the first remaining player, required when the
+    /// owner leaves a lobby others are still in - they become the owner
+    pub next_owner: Option<Account<'info, Player>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
@@ -1,39 +1,23 @@
 use anchor_lang::prelude::*;
 use crate::error::InitGameError;
-use crate::constants::{GAME_SEED, PLAYER_SEED, MAX_PLAYERS_PER_TEAM};
+use crate::state::{Game, LobbyDirectory};
+use crate::constants::{GAME_SEED, LOBBY_DIRECTORY_SEED, PLAYER_SEED};
+use map_registry::{MapMetadata, MapRevision, MAP_METADATA_SEED, MAP_REVISION_SEED};
 
-pub fn handler(ctx: Context<InitGame>, map_id: String) -> Result<()> {
+pub fn handler(ctx: Context<InitGame>, map_id: String, is_private: bool) -> Result<()> {
     let game = &mut ctx.accounts.game;
     let player = &mut ctx.accounts.player;
     let clock = Clock::get()?;
 
     require!(player.has_logged_in, InitGameError::PlayerNotRegistered);
     require!(player.current_game.is_none(), InitGameError::PlayerAlreadyInGame);
+    require!(!player.is_queued(clock.unix_timestamp), InitGameError::PlayerInQueue);
 
     // Initialize game state - the game account itself is the PDA that tracks the room
-    game.match_start_timestamp = clock.unix_timestamp;
-    game.game_state = 0; // waiting state
-    game.team_a_score = 0;
-    game.team_b_score = 0;
-    game.team_a_kills = 0;
-    game.team_b_kills = 0;
-    game.current_players_team_a = 0;
-    game.current_players_team_b = 0;
-    game.match_duration = 300; // 5 minutes
-    game.max_players_per_team = MAX_PLAYERS_PER_TEAM;
-    game.match_type = 1; // team deathmatch
-    game.map_id = map_id;
-    
-    // NEW: Initialize lobby features
-    game.lobby_name = "New Game Room".to_string();
-    game.created_by = ctx.accounts.authority.key();
-    game.is_private = false;
-    game.ready_players = 0;
-    game.map_selection = 0;
-
-    // Initialize player tracking vectors
-    game.team_a_players = Vec::new();
-    game.team_b_players = Vec::new();
+    game.init_lobby(ctx.accounts.authority.key(), clock.unix_timestamp);
+    // Set before the map - private lobbies may also use Unlisted maps
+    game.is_private = is_private;
+    game.set_map(map_id, &ctx.accounts.map_metadata, &ctx.accounts.map_revision)?;
 
     // Add the room creator as the first player in Team A
     let game_key = game.key();
@@ -45,31 +29,34 @@ pub fn handler(ctx: Context<InitGame>, map_id: String) -> Result<()> {
     player.current_game = Some(game_key);
 
     // Add creator to Team A players
-    game.team_a_players.push(player_key);
-    game.current_players_team_a = 1;
+    game.add_player_to_team(player_key, 1)?;
 
     // Increment the game counter for this player
     player.game_counter += 1;
 
+    // List public lobbies in the directory so they can be browsed without the address
+    let lobby_directory = &mut ctx.accounts.lobby_directory;
+    game.lobby_page = lobby_directory.page;
+    game.is_listed = false;
+    if !game.is_private {
+        lobby_directory.upsert(game_key, game)?;
+        game.is_listed = true;
+    }
+
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(map_id: String)]
 pub struct InitGame<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + // discriminator
-                4 + 4 + 4 + 4 + 4 + 8 + 1 + 8 + 1 + 1 + 1 + 1 + 1 + // basic game fields (scores, kills, duration, timestamps, state, team counts, winning_team, match_type)
-                (4 + 50) + // map_id String (4 byte length + up to 50 chars)
-                (4 + 32) + // lobby_name string with length prefix
-                32 + // created_by pubkey
-                1 + 1 + 1 + // is_private + ready_players + map_selection
-                (4 + 32 * 5) + (4 + 32 * 5), // team_a_players Vec (4 byte length + max 5 pubkeys) + team_b_players Vec
+        space = Game::SIZE,
         seeds = [GAME_SEED.as_bytes(), authority.key().as_ref(), &player.game_counter.to_le_bytes()],
         bump
     )]
-    pub game: Account<'info, crate::state::Game>,
+    pub game: Account<'info, Game>,
     
     #[account(
         mut,
@@ -77,6 +64,35 @@ pub struct InitGame<'info> {
         bump
     )]
     pub player: Account<'info, crate::state::Player>,
+
+    /// Directory page the lobby gets listed on - any initialized page with room
+    #[account(
+        mut,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &lobby_directory.page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Account<'info, LobbyDirectory>,
+
+    /// Registry entry for the selected map, proves the map exists
+    #[account(
+        seeds = [MAP_METADATA_SEED, map_id.as_bytes()],
+        bump,
+        seeds::program = map_registry::ID
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// Latest published revision of the map, the lobby is pinned to it
+    #[account(
+        seeds = [
+            MAP_REVISION_SEED,
+            map_id.as_bytes(),
+            &map_metadata.nonce.to_le_bytes(),
+            &map_metadata.published_version.to_le_bytes()
+        ],
+        bump,
+        seeds::program = map_registry::ID
+    )]
+    pub map_revision: Account<'info, MapRevision>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
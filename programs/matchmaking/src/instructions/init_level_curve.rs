@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::error::ConfigError;
+use crate::state::{LevelCurve, MatchmakingConfig};
+use crate::constants::{
+    CONFIG_SEED, LEVEL_CURVE_SEED, DEFAULT_LEVEL_COUNT, DEFAULT_XP_PER_KILL,
+    DEFAULT_XP_PER_SCORE_POINT, DEFAULT_XP_PER_WIN, DEFAULT_XP_PER_MATCH,
+};
+
+/// Create the level curve with default rewards and thresholds
+/// Level L (L >= 2) requires 50 * (L - 1) * L total XP
+pub fn handler(ctx: Context<InitLevelCurve>) -> Result<()> {
+    let level_curve = &mut ctx.accounts.level_curve;
+
+    level_curve.xp_per_kill = DEFAULT_XP_PER_KILL;
+    level_curve.xp_per_score_point = DEFAULT_XP_PER_SCORE_POINT;
+    level_curve.xp_per_win = DEFAULT_XP_PER_WIN;
+    level_curve.xp_per_match = DEFAULT_XP_PER_MATCH;
+    level_curve.level_thresholds = (2..=DEFAULT_LEVEL_COUNT)
+        .map(|level| 50 * (level - 1) * level)
+        .collect();
+
+    msg!("Level curve initialized with {} levels", DEFAULT_LEVEL_COUNT);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitLevelCurve<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = LevelCurve::SIZE,
+        seeds = [LEVEL_CURVE_SEED.as_bytes()],
+        bump
+    )]
+    pub level_curve: Account<'info, LevelCurve>,
+
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump,
+        has_one = admin @ ConfigError::Unauthorized
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::LobbyDirectory;
+use crate::constants::LOBBY_DIRECTORY_SEED;
+
+/// Create a page of the global lobby directory
+/// Permissionless - whoever needs a fresh page pays for it
+pub fn handler(ctx: Context<InitLobbyDirectory>, page: u32) -> Result<()> {
+    let lobby_directory = &mut ctx.accounts.lobby_directory;
+
+    lobby_directory.page = page;
+    lobby_directory.entries = Vec::new();
+
+    msg!("Lobby directory page {} initialized", page);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct InitLobbyDirectory<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = LobbyDirectory::SIZE,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Account<'info, LobbyDirectory>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::MatchQueue;
+use crate::constants::QUEUE_SEED;
+
+/// Create the matchmaking queue for a (mode, region) pair
+/// Permissionless - there is exactly one queue per pair
+pub fn handler(ctx: Context<InitMatchQueue>, mode: u8, region: u8) -> Result<()> {
+    let queue = &mut ctx.accounts.queue;
+
+    queue.mode = mode;
+    queue.region = region;
+    queue.games_created = 0;
+    queue.entries = Vec::new();
+
+    msg!("Match queue initialized for mode {} region {}", mode, region);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(mode: u8, region: u8)]
+pub struct InitMatchQueue<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = MatchQueue::SIZE,
+        seeds = [QUEUE_SEED.as_bytes(), &[mode], &[region]],
+        bump
+    )]
+    pub queue: Account<'info, MatchQueue>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
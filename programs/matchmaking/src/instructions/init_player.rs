@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::error::InitPlayerError;
-use crate::constants::PLAYER_SEED;
+use crate::state::{parse_username, username_seed, validate_username, UsernameRecord};
+use crate::constants::{PLAYER_SEED, USERNAME_SEED, DEFAULT_RATING};
 
 pub fn handler(ctx: Context<InitPlayer>, args: Vec<u8>) -> Result<()> {
     let clock = Clock::get()?;
@@ -8,22 +9,14 @@ pub fn handler(ctx: Context<InitPlayer>, args: Vec<u8>) -> Result<()> {
 
     require!(!player.has_logged_in, InitPlayerError::AlreadyLoggedIn);
     
-    let username = if args.is_empty() {
-        let timestamp_suffix = (clock.unix_timestamp as u32) % 100000;
-        format!("Player{}", timestamp_suffix)
-    } else {
-        let username_len = args[0] as usize;
-        if username_len == 0 || username_len > 32 || args.len() < 1 + username_len {
-            return Err(InitPlayerError::InvalidUsername.into());
-        }
-        
-        let username_bytes = &args[1..1 + username_len];
-        String::from_utf8(username_bytes.to_vec())
-            .map_err(|_| InitPlayerError::InvalidUsername)?
-    };
-    
-    require!(username.len() >= 3 && username.len() <= 32, InitPlayerError::InvalidUsernameLength);
-    
+    let username = parse_username(&args, &ctx.accounts.authority.key())?;
+    validate_username(&username)?;
+
+    // Claim the name - the record PDA already existing means it is taken
+    let username_record = &mut ctx.accounts.username_record;
+    username_record.owner = ctx.accounts.authority.key();
+    username_record.username = username.clone();
+
     player.authority = ctx.accounts.authority.key();
     player.signing_key = ctx.accounts.signing_key.key();
     msg!("Player Signing Key is already ready {}", player.signing_key);
@@ -49,10 +42,24 @@ pub fn handler(ctx: Context<InitPlayer>, args: Vec<u8>) -> Result<()> {
     player.rotation_y = 0.0;
     player.rotation_z = 0.0;
 
+    // Not waiting in any matchmaking queue
+    player.current_queue = None;
+    player.queued_at = 0;
+
+    player.rating = DEFAULT_RATING;
+    player.xp = 0;
+
+    player.season_id = 0;
+    player.season_kills = 0;
+    player.season_wins = 0;
+
+    player.last_rename_timestamp = 0;
+
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(args: Vec<u8>)]
 pub struct InitPlayer<'info> {
     #[account(
         init,
@@ -62,11 +69,24 @@ pub struct InitPlayer<'info> {
                 (4 + 32) + // username string
                 1 + 1 + (1 + 32) + 1 + 8 + 4 + 4 + // has_logged_in, team, current_game, is_alive, last_login_timestamp, total_matches_played, level
                 1 + 1 + 4 + // is_ready, is_spectator, game_counter
-                4 + 4 + 4 + 4 + 4 + 4, // position (x,y,z) + rotation (x,y,z) - 6 f32 fields
+                4 + 4 + 4 + 4 + 4 + 4 + // position (x,y,z) + rotation (x,y,z) - 6 f32 fields
+                (1 + 32) + 8 + // current_queue + queued_at
+                4 + 8 + // rating + xp
+                4 + 4 + 4 + // season_id + season_kills + season_wins
+                8, // last_rename_timestamp
         seeds = [PLAYER_SEED.as_bytes(), authority.key().as_ref()],
         bump
     )]
     pub player: Account<'info, crate::state::Player>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = UsernameRecord::SIZE,
+        seeds = [USERNAME_SEED.as_bytes(), &username_seed(&args, &authority.key())],
+        bump
+    )]
+    pub username_record: Account<'info, UsernameRecord>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
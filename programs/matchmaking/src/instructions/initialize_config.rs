@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use crate::error::ConfigError;
+use crate::state::MatchmakingConfig;
+use crate::constants::{CONFIG_SEED, DEFAULT_LOBBY_IDLE_TIMEOUT};
+
+/// Create the global matchmaking config
+/// Can only be called once, by the program's upgrade authority, who becomes the admin
+pub fn handler(ctx: Context<InitializeConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.admin = ctx.accounts.admin.key();
+    config.current_season = 0;
+    config.season_active = false;
+    config.lobby_idle_timeout = DEFAULT_LOBBY_IDLE_TIMEOUT;
+
+    msg!("Matchmaking config initialized with admin {}", config.admin);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = MatchmakingConfig::SIZE,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    /// This program's ProgramData, keeps whoever lands first at deploy from claiming the admin role
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ConfigError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
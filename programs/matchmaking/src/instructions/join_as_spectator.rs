@@ -9,9 +9,10 @@ pub fn handler(ctx: Context<JoinAsSpectator>) -> Result<()> {
 
     require!(player.has_logged_in, JoinGameError::PlayerNotRegistered);
     require!(player.current_game.is_none(), JoinGameError::PlayerAlreadyInGame);
+    require!(!player.is_queued(Clock::get()?.unix_timestamp), JoinGameError::PlayerInQueue);
 
     // Can join as spectator even if game started
-    require!(game.game_state <= 1, JoinGameError::InvalidGameState); // 0=waiting, 1=active
+    require!(game.game_state <= 1 || game.is_paused(), JoinGameError::InvalidGameState); // 0=waiting, 1=active, 3=paused
 
     // Set player as spectator
     player.current_game = Some(game.key());
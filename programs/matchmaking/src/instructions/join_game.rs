@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::error::JoinGameError;
-use crate::constants::{PLAYER_SEED, MAX_TOTAL_PLAYERS, MAX_PLAYERS_PER_TEAM};
+use crate::state::LobbyDirectory;
+use crate::constants::{PLAYER_SEED, LOBBY_DIRECTORY_SEED};
 
 pub fn handler(ctx: Context<JoinGame>) -> Result<()> {
     let player = &mut ctx.accounts.player;
@@ -8,41 +9,22 @@ pub fn handler(ctx: Context<JoinGame>) -> Result<()> {
 
     require!(player.has_logged_in, JoinGameError::PlayerNotRegistered);
     require!(player.current_game.is_none(), JoinGameError::PlayerAlreadyInGame);
+    let now = Clock::get()?.unix_timestamp;
+    require!(!player.is_queued(now), JoinGameError::PlayerInQueue);
     require!(game.game_state == 0, JoinGameError::GameAlreadyStarted);
 
-    // Check if game has space
-    let total_players = game.current_players_team_a + game.current_players_team_b;
-    require!(total_players < MAX_TOTAL_PLAYERS, JoinGameError::GameFull);
-
-    // Determine which team to join (balance teams)
-    let team = if game.current_players_team_a <= game.current_players_team_b { 1 } else { 2 };
-
-    // Check if the selected team has space
-    if team == 1 {
-        require!(
-            game.team_a_players.len() < MAX_PLAYERS_PER_TEAM as usize,
-            JoinGameError::TeamFull
-        );
-    } else {
-        require!(
-            game.team_b_players.len() < MAX_PLAYERS_PER_TEAM as usize,
-            JoinGameError::TeamFull
-        );
-    }
+    // Join the smaller team
+    let team = game.add_player(player.key())?;
 
     player.is_alive = true;
     player.team = team;
     player.current_game = Some(game.key());
     player.is_ready = false; // Reset ready state when joining
+    game.last_activity = now;
 
-    // Add player PDA to the appropriate team vector
-    if player.team == 1 {
-        game.team_a_players.push(player.key());
-        game.current_players_team_a += 1;
-    } else {
-        game.team_b_players.push(player.key());
-        game.current_players_team_b += 1;
-    }
+    // Refresh the player counts shown in the lobby directory, full lobbies drop out
+    let game_key = game.key();
+    game.sync_listing(game_key, ctx.accounts.lobby_directory.as_deref_mut())?;
 
     Ok(())
 }
@@ -58,6 +40,14 @@ pub struct JoinGame<'info> {
         bump
     )]
     pub player: Account<'info, crate::state::Player>,
+
+    /// Directory page the lobby is listed on, required while the lobby is listed
+    #[account(
+        mut,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &game.lobby_page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Option<Account<'info, LobbyDirectory>>,
     
     pub authority: Signer<'info>,
 }
\ No newline at end of file
@@ -1,11 +1,13 @@
 
 use anchor_lang::prelude::*;
 use crate::error::LeaveGameError;
-use crate::constants::PLAYER_SEED;
+use crate::state::LobbyDirectory;
+use crate::constants::{PLAYER_SEED, LOBBY_DIRECTORY_SEED, RECONNECT_GRACE_PERIOD};
 
 pub fn handler(ctx: Context<LeaveGame>) -> Result<()> {
     let game = &mut ctx.accounts.game;
     let player = &mut ctx.accounts.player;
+    let clock = Clock::get()?;
 
     require!(player.has_logged_in, LeaveGameError::PlayerNotInGame);
     require!(player.current_game.is_some(), LeaveGameError::PlayerNotInGame);
@@ -32,50 +34,56 @@ pub fn handler(ctx: Context<LeaveGame>) -> Result<()> {
         game.current_players_team_b = game.current_players_team_b.saturating_sub(1);
     }
 
+    // Hold the slot so a dropped client can reconnect to the running match
+    if (game.game_state == 1 || game.is_paused()) && player.team != 0 {
+        game.reserve_slot(player_key, player.team, clock.unix_timestamp + RECONNECT_GRACE_PERIOD);
+    }
+
+    if player.is_ready {
+        game.ready_players = game.ready_players.saturating_sub(1);
+    }
+
     player.is_alive = false;
+    player.is_ready = false;
     player.team = 0;
     player.current_game = None;
+    game.last_activity = clock.unix_timestamp;
 
     let total_players = game.current_players_team_a.checked_add(game.current_players_team_b)
         .ok_or(LeaveGameError::ArithmeticOverflow)?;
 
-    // Handle owner leaving
-    if is_owner {
-        // If there are other players remaining, transfer ownership to the first remaining player
-        if total_players > 0 {
-            // Find the first remaining player to become the new owner
-            let new_owner = if !game.team_a_players.is_empty() {
-                game.team_a_players[0]
-            } else {
-                game.team_b_players[0]
-            };
-
-            game.created_by = new_owner;
-            msg!("Owner left. Ownership transferred to: {}", new_owner);
-        } else {
-            // No players left, close the game account (room gets deleted)
-            let game_info = game.to_account_info();
-            let authority_info = ctx.accounts.authority.to_account_info();
-
-            let game_lamports = game_info.lamports();
-            let authority_lamports = authority_info.lamports();
-
-            // Transfer lamports from game to authority
-            **authority_info.try_borrow_mut_lamports()? = authority_lamports
-                .checked_add(game_lamports)
-                .ok_or(LeaveGameError::ArithmeticOverflow)?;
-
-            // Close the account by zeroing lamports - this is intentional for account closure
-            #[allow(clippy::manual_lamports_zeroing)]
-            {
-                **game_info.try_borrow_mut_lamports()? = 0;
-            }
-            msg!("Game closed - no players remaining");
+    // Sync the directory and hand off ownership
+    let game_key = game.key();
+    let close_game = game.after_player_left(
+        game_key,
+        is_owner,
+        ctx.accounts.lobby_directory.as_deref_mut(),
+        ctx.accounts.next_owner.as_ref(),
+    )?;
+
+    if close_game {
+        // No players left, close the game account (room gets deleted)
+        let game_info = game.to_account_info();
+        let authority_info = ctx.accounts.authority.to_account_info();
+
+        let game_lamports = game_info.lamports();
+        let authority_lamports = authority_info.lamports();
+
+        // Transfer lamports from game to authority
+        **authority_info.try_borrow_mut_lamports()? = authority_lamports
+            .checked_add(game_lamports)
+            .ok_or(LeaveGameError::ArithmeticOverflow)?;
+
+        // Close the account by zeroing lamports - this is intentional for account closure
+        #[allow(clippy::manual_lamports_zeroing)]
+        {
+            **game_info.try_borrow_mut_lamports()? = 0;
         }
-    } else if total_players == 0 && game.game_state == 1 {
+        msg!("Game closed - no players remaining");
+    } else if total_players == 0 && (game.game_state == 1 || game.is_paused()) {
         // If all players left during an active game, end the game
         game.game_state = 2;
-        game.match_end_timestamp = Some(Clock::get()?.unix_timestamp);
+        game.match_end_timestamp = Some(clock.unix_timestamp);
     }
 
     Ok(())
@@ -92,6 +100,19 @@ pub struct LeaveGame<'info> {
         bump
     )]
     pub player: Account<'info, crate::state::Player>,
+
+    /// Directory page the lobby is listed on, required while the lobby is listed
+    /// or when a full public lobby frees a slot and is listed again
+    #[account(
+        mut,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &game.lobby_page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Option<Account<'info, LobbyDirectory>>,
+
+    /// Player account of the first remaining player, required when the
+    /// owner leaves a lobby others are still in - they become the owner
+    pub next_owner: Option<Account<'info, crate::state::Player>>,
     
     pub authority: Signer<'info>,
 }
\ No newline at end of file
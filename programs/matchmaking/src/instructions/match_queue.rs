@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use crate::error::QueueError;
+use crate::state::{Game, MatchQueue, Player};
+use crate::constants::{GAME_SEED, MAX_TOTAL_PLAYERS, MIN_PLAYERS_TO_START};
+use map_registry::{MapMetadata, MapRevision, MAP_METADATA_SEED, MAP_REVISION_SEED};
+
+/// Permissionless crank - creates a Game for a batch of queued players
+/// Remaining accounts: the Player PDAs to match (writable), all waiting in this queue
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MatchFromQueue<'info>>,
+    map_id: String,
+) -> Result<()> {
+    let queue = &mut ctx.accounts.queue;
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+
+    let player_infos = ctx.remaining_accounts;
+    require!(
+        player_infos.len() >= MIN_PLAYERS_TO_START as usize,
+        QueueError::NotEnoughPlayers
+    );
+    require!(
+        player_infos.len() <= MAX_TOTAL_PLAYERS as usize,
+        QueueError::TooManyPlayers
+    );
+
+    // The first matched player owns the room so they can start it
+    let first_player = Account::<Player>::try_from(&player_infos[0])?;
+    game.init_lobby(first_player.authority, now);
+    game.set_map(map_id, &ctx.accounts.map_metadata, &ctx.accounts.map_revision)?;
+    game.match_type = queue.mode;
+
+    queue.prune_stale(now);
+    seat_queued_players(queue, game, player_infos)?;
+    queue.games_created += 1;
+
+    msg!(
+        "Matched {} players into game {} (team A: {}, team B: {})",
+        player_infos.len(),
+        game.key(),
+        game.current_players_team_a,
+        game.current_players_team_b
+    );
+
+    Ok(())
+}
+
+/// Move queued players into the game, balancing teams with the join_game bookkeeping
+pub(crate) fn seat_queued_players<'info>(
+    queue: &mut Account<'info, MatchQueue>,
+    game: &mut Account<'info, Game>,
+    player_infos: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let queue_key = queue.key();
+    let game_key = game.key();
+
+    for player_info in player_infos {
+        let mut player = Account::<Player>::try_from(player_info)?;
+
+        require!(player.current_game.is_none(), QueueError::PlayerAlreadyInGame);
+        require!(player.current_queue == Some(queue_key), QueueError::PlayerNotQueued);
+
+        // Stale entries were pruned already, so a missing entry means the player timed out
+        queue.take(&player.key()).ok_or(QueueError::PlayerNotQueued)?;
+
+        let team = game.add_player(player.key())?;
+
+        player.is_alive = true;
+        player.team = team;
+        player.is_ready = false;
+        player.current_game = Some(game_key);
+        player.current_queue = None;
+        player.queued_at = 0;
+
+        player.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(map_id: String)]
+pub struct MatchFromQueue<'info> {
+    #[account(mut)]
+    pub queue: Account<'info, MatchQueue>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = Game::SIZE,
+        seeds = [GAME_SEED.as_bytes(), queue.key().as_ref(), &queue.games_created.to_le_bytes()],
+        bump
+    )]
+    pub game: Account<'info, Game>,
+
+    /// Registry entry for the map the match is played on
+    #[account(
+        seeds = [MAP_METADATA_SEED, map_id.as_bytes()],
+        bump,
+        seeds::program = map_registry::ID
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// Latest published revision of the map, the lobby is pinned to it
+    #[account(
+        seeds = [
+            MAP_REVISION_SEED,
+            map_id.as_bytes(),
+            &map_metadata.nonce.to_le_bytes(),
+            &map_metadata.published_version.to_le_bytes()
+        ],
+        bump,
+        seeds::program = map_registry::ID
+    )]
+    pub map_revision: Account<'info, MapRevision>,
+
+    /// Crank caller, pays for the new Game account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -5,6 +5,32 @@ pub mod join_game;
 pub mod join_as_spectator;
 pub mod leave_game;
 pub mod set_ready_state;
+pub mod init_lobby_directory;
+pub mod init_match_queue;
+pub mod enqueue;
+pub mod dequeue;
+pub mod match_queue;
+pub mod fill_from_queue;
+pub mod end_game;
+pub mod record_player_result;
+pub mod settle_match;
+pub mod balance_teams;
+pub mod initialize_config;
+pub mod init_level_curve;
+pub mod update_level_curve;
+pub mod open_season;
+pub mod close_season;
+pub mod pause_match;
+pub mod resume_match;
+pub mod update_config;
+pub mod close_stale_lobby;
+pub mod force_leave_stale_game;
+pub mod rejoin_game;
+pub mod set_idle_timeout;
+pub mod remove_idle_player;
+pub mod rename_player;
+pub mod close_player;
+pub mod update_lobby_settings;
 
 pub use init_player::*;
 pub use init_game::*;
@@ -12,4 +38,30 @@ pub use start_game::*;
 pub use join_game::*;
 pub use join_as_spectator::*;
 pub use leave_game::*;
-pub use set_ready_state::*;
\ No newline at end of file
+pub use set_ready_state::*;
+pub use init_lobby_directory::*;
+pub use init_match_queue::*;
+pub use enqueue::*;
+pub use dequeue::*;
+pub use match_queue::*;
+pub use fill_from_queue::*;
+pub use end_game::*;
+pub use record_player_result::*;
+pub use settle_match::*;
+pub use balance_teams::*;
+pub use initialize_config::*;
+pub use init_level_curve::*;
+pub use update_level_curve::*;
+pub use open_season::*;
+pub use close_season::*;
+pub use pause_match::*;
+pub use resume_match::*;
+pub use update_config::*;
+pub use close_stale_lobby::*;
+pub use force_leave_stale_game::*;
+pub use rejoin_game::*;
+pub use set_idle_timeout::*;
+pub use remove_idle_player::*;
+pub use rename_player::*;
+pub use close_player::*;
+pub use update_lobby_settings::*;
\ No newline at end of file
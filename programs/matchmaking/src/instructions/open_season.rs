@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::error::{ConfigError, SeasonError};
+use crate::state::{MatchmakingConfig, Season};
+use crate::constants::{CONFIG_SEED, SEASON_SEED};
+
+/// Open the next ranked season - admin only
+pub fn handler(ctx: Context<OpenSeason>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let season = &mut ctx.accounts.season;
+
+    require!(!config.season_active, SeasonError::SeasonAlreadyActive);
+
+    config.current_season += 1;
+    config.season_active = true;
+
+    season.season_id = config.current_season;
+    season.started_at = Clock::get()?.unix_timestamp;
+    season.ended_at = None;
+    season.is_active = true;
+    season.top_rating = Vec::new();
+    season.top_kills = Vec::new();
+    season.top_wins = Vec::new();
+
+    msg!("Season {} opened", season.season_id);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct OpenSeason<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump,
+        has_one = admin @ ConfigError::Unauthorized
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Season::SIZE,
+        seeds = [SEASON_SEED.as_bytes(), &(config.current_season + 1).to_le_bytes()],
+        bump
+    )]
+    pub season: Account<'info, Season>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
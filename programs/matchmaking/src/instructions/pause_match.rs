@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::error::PauseError;
+use crate::state::Game;
+
+/// Pause an active match - lobby owner only
+/// Game program input is rejected and the match clock stops until resume_match
+pub fn handler(ctx: Context<PauseMatch>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+
+    require!(
+        ctx.accounts.authority.key() == game.created_by,
+        PauseError::NotLobbyOwner
+    );
+    require!(game.game_state == 1, PauseError::GameNotActive);
+
+    game.game_state = 3;
+    game.paused_at = Clock::get()?.unix_timestamp;
+
+    msg!("Game {} paused", game.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PauseMatch<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    pub authority: Signer<'info>,
+}
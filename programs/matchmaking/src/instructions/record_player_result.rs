@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use crate::error::SettlementError;
+use crate::state::{Game, LevelCurve, MatchRecord, Player, PlayerMatchHistory, PlayerStatLine};
+use crate::constants::{
+    SETTLEMENT_AUTHORITY_SEED, GAME_PROGRAM_ID, LEVEL_CURVE_SEED, MATCH_RECORD_SEED, MATCH_HISTORY_SEED,
+};
+
+/// Record one player's final stats for an ended match
+/// CPI only - signed by the game program's settlement PDA after the GamePlayer is undelegated
+pub fn handler(ctx: Context<RecordPlayerResult>, kills: u32, deaths: u32, score: u32) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let player = &mut ctx.accounts.player;
+    let level_curve = &ctx.accounts.level_curve;
+
+    require!(game.game_state == 2, SettlementError::GameNotEnded);
+    require!(!game.is_settled, SettlementError::AlreadySettled);
+
+    // Attribute to the team matchmaking rostered the player on
+    let team = game.team_of(&player.key()).ok_or(SettlementError::PlayerNotInGame)?;
+    if team == 1 {
+        game.team_a_kills = game.team_a_kills.saturating_add(kills);
+        game.team_a_score = game.team_a_score.saturating_add(score);
+    } else {
+        game.team_b_kills = game.team_b_kills.saturating_add(kills);
+        game.team_b_score = game.team_b_score.saturating_add(score);
+    }
+    game.results_reported = game.results_reported.saturating_add(1);
+
+    // Stat line in the durable match record, linked from the player's history
+    let match_record = &mut ctx.accounts.match_record;
+    match_record.init_if_new(game.key(), game);
+    require!(
+        !match_record.players.iter().any(|line| line.player == player.key()),
+        SettlementError::ResultAlreadyRecorded
+    );
+    match_record.players.push(PlayerStatLine {
+        player: player.key(),
+        authority: player.authority,
+        team,
+        kills,
+        deaths,
+        score,
+    });
+    ctx.accounts.match_history.push(player.authority, match_record.key());
+
+    // Participation, kill and score XP - the win bonus is awarded at settlement
+    let xp = (level_curve.xp_per_match as u64)
+        .saturating_add(kills as u64 * level_curve.xp_per_kill as u64)
+        .saturating_add(score as u64 * level_curve.xp_per_score_point as u64);
+    let player_key = player.key();
+    player.award_xp(player_key, xp, level_curve);
+
+    msg!(
+        "Recorded result for player {} - Kills: {}, Deaths: {}, Score: {} ({}/{} reported)",
+        player.key(),
+        kills,
+        deaths,
+        score,
+        game.results_reported,
+        game.total_players()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RecordPlayerResult<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    #[account(
+        mut,
+        constraint = player.current_game == Some(game.key()) @ SettlementError::PlayerNotInGame
+    )]
+    pub player: Account<'info, Player>,
+
+    #[account(
+        seeds = [LEVEL_CURVE_SEED.as_bytes()],
+        bump
+    )]
+    pub level_curve: Account<'info, LevelCurve>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MatchRecord::SIZE,
+        seeds = [MATCH_RECORD_SEED.as_bytes(), game.key().as_ref()],
+        bump
+    )]
+    pub match_record: Account<'info, MatchRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PlayerMatchHistory::SIZE,
+        seeds = [MATCH_HISTORY_SEED.as_bytes(), player.authority.as_ref()],
+        bump
+    )]
+    pub match_history: Account<'info, PlayerMatchHistory>,
+
+    /// Game program PDA - proves the stats come from the player's GamePlayer account
+    #[account(
+        seeds = [SETTLEMENT_AUTHORITY_SEED.as_bytes()],
+        bump,
+        seeds::program = GAME_PROGRAM_ID
+    )]
+    pub settlement_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::error::RejoinError;
+use crate::state::{Game, Player};
+use crate::constants::PLAYER_SEED;
+
+/// Reconnect to a running match within the grace period after leaving it
+/// The player goes back on the team they left; init_game_player then
+/// reuses their GamePlayer in the game program
+pub fn handler(ctx: Context<RejoinGame>) -> Result<()> {
+    let player = &mut ctx.accounts.player;
+    let game = &mut ctx.accounts.game;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(player.has_logged_in, RejoinError::PlayerNotRegistered);
+    require!(player.current_game.is_none(), RejoinError::PlayerAlreadyInGame);
+    require!(
+        game.game_state == 1 || game.is_paused(),
+        RejoinError::MatchNotInProgress
+    );
+
+    let slot = game.take_reserved_slot(&player.key())
+        .ok_or(RejoinError::NoReservedSlot)?;
+    require!(now <= slot.expires_at, RejoinError::ReservationExpired);
+
+    game.add_player_to_team(player.key(), slot.team)?;
+
+    player.is_alive = true;
+    player.team = slot.team;
+    player.current_game = Some(game.key());
+    player.is_ready = true;
+    game.ready_players = game.ready_players.saturating_add(1);
+    player.is_spectator = false;
+    game.last_activity = now;
+
+    msg!("Player {} rejoined game {} on team {}", player.key(), game.key(), slot.team);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RejoinGame<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub player: Account<'info, Player>,
+
+    pub authority: Signer<'info>,
+}
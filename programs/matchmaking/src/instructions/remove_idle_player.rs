@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::error::IdleError;
+use crate::state::{Game, Player};
+use crate::constants::{SETTLEMENT_AUTHORITY_SEED, GAME_PROGRAM_ID, RECONNECT_GRACE_PERIOD};
+
+/// Take an AFK player off their team in a running match
+/// CPI only - signed by the game program's settlement PDA once the GamePlayer
+/// has been flagged idle and undelegated
+/// The slot is held like a leave_game drop-out, so the player can reconnect
+/// and is rated as a loss if they don't
+pub fn handler(ctx: Context<RemoveIdlePlayer>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let player = &mut ctx.accounts.player;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(
+        game.game_state == 1 || game.is_paused(),
+        IdleError::MatchNotInProgress
+    );
+    let team = game.team_of(&player.key()).ok_or(IdleError::PlayerNotInGame)?;
+
+    game.remove_player(&player.key());
+    game.reserve_slot(player.key(), team, now + RECONNECT_GRACE_PERIOD);
+    if player.is_ready {
+        game.ready_players = game.ready_players.saturating_sub(1);
+    }
+    game.last_activity = now;
+
+    player.current_game = None;
+    player.team = 0;
+    player.is_alive = false;
+    player.is_ready = false;
+
+    msg!(
+        "Idle player {} removed from game {} (team A: {}, team B: {})",
+        player.key(),
+        game.key(),
+        game.current_players_team_a,
+        game.current_players_team_b
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveIdlePlayer<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    #[account(
+        mut,
+        constraint = player.current_game == Some(game.key()) @ IdleError::PlayerNotInGame
+    )]
+    pub player: Account<'info, Player>,
+
+    /// Game program PDA - only the game program can kick idle players
+    #[account(
+        seeds = [SETTLEMENT_AUTHORITY_SEED.as_bytes()],
+        bump,
+        seeds::program = GAME_PROGRAM_ID
+    )]
+    pub settlement_authority: Signer<'info>,
+}
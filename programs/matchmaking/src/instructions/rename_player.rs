@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::error::RenameError;
+use crate::state::{normalize_username, validate_username, Player, UsernameRecord};
+use crate::constants::{PLAYER_SEED, USERNAME_SEED, RENAME_COOLDOWN};
+
+/// Change a player's username
+/// Releases the old name and claims the new one in the same transaction
+/// A change of capitalization only keeps the existing claim and updates its
+/// display name - new_username_record is omitted then
+pub fn handler(ctx: Context<RenamePlayer>, new_username: String) -> Result<()> {
+    let player = &mut ctx.accounts.player;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(player.has_logged_in, RenameError::PlayerNotRegistered);
+    require!(
+        player.last_rename_timestamp == 0
+            || now.saturating_sub(player.last_rename_timestamp) >= RENAME_COOLDOWN,
+        RenameError::RenameCooldownActive
+    );
+    validate_username(&new_username)?;
+
+    let old_record = &mut ctx.accounts.old_username_record;
+    if normalize_username(&player.username) == normalize_username(&new_username) {
+        old_record.username = new_username.clone();
+    } else {
+        let new_record = ctx.accounts.new_username_record.as_mut()
+            .ok_or(RenameError::UsernameRecordRequired)?;
+        new_record.owner = ctx.accounts.authority.key();
+        new_record.username = new_username.clone();
+
+        old_record.close(ctx.accounts.authority.to_account_info())?;
+    }
+
+    msg!("Player {} renamed from {} to {}", player.key(), player.username, new_username);
+
+    player.username = new_username;
+    player.last_rename_timestamp = now;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(new_username: String)]
+pub struct RenamePlayer<'info> {
+    #[account(
+        mut,
+        seeds = [PLAYER_SEED.as_bytes(), authority.key().as_ref()],
+        bump,
+        constraint = player.username != new_username @ RenameError::SameUsername
+    )]
+    pub player: Account<'info, Player>,
+
+    /// Record for the current name, closed to release it
+    #[account(
+        mut,
+        seeds = [USERNAME_SEED.as_bytes(), &normalize_username(&player.username)],
+        bump,
+        constraint = old_username_record.owner == authority.key() @ RenameError::NotUsernameOwner
+    )]
+    pub old_username_record: Account<'info, UsernameRecord>,
+
+    /// Record for the new name, required unless only the capitalization changes
+    #[account(
+        init,
+        payer = authority,
+        space = UsernameRecord::SIZE,
+        seeds = [USERNAME_SEED.as_bytes(), &normalize_username(&new_username)],
+        bump
+    )]
+    pub new_username_record: Option<Account<'info, UsernameRecord>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::error::PauseError;
+use crate::state::Game;
+
+/// Resume a paused match - lobby owner only
+pub fn handler(ctx: Context<ResumeMatch>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let clock = Clock::get()?;
+
+    require!(
+        ctx.accounts.authority.key() == game.created_by,
+        PauseError::NotLobbyOwner
+    );
+    require!(game.is_paused(), PauseError::GameNotPaused);
+
+    let paused_for = clock.unix_timestamp.saturating_sub(game.paused_at);
+    game.total_paused_time += paused_for;
+    game.paused_at = 0;
+    game.resumed_at = clock.unix_timestamp;
+    game.game_state = 1;
+
+    msg!(
+        "Game {} resumed after {} seconds (total paused: {})",
+        game.key(),
+        paused_for,
+        game.total_paused_time
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ResumeMatch<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    pub authority: Signer<'info>,
+}
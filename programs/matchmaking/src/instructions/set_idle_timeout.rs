@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::error::IdleError;
+use crate::state::Game;
+use crate::constants::MIN_IDLE_TIMEOUT;
+
+/// Set how long a player may go without input before being kicked - lobby owner only
+pub fn handler(ctx: Context<SetIdleTimeout>, idle_timeout: i64) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+
+    require!(
+        ctx.accounts.authority.key() == game.created_by,
+        IdleError::NotLobbyOwner
+    );
+    require!(game.game_state == 0, IdleError::GameAlreadyStarted);
+    require!(idle_timeout >= MIN_IDLE_TIMEOUT, IdleError::IdleTimeoutTooShort);
+
+    game.idle_timeout = idle_timeout;
+    game.last_activity = Clock::get()?.unix_timestamp;
+
+    msg!("Game {} idle timeout set to {}s", game.key(), idle_timeout);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetIdleTimeout<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    pub authority: Signer<'info>,
+}
@@ -21,6 +21,7 @@ pub fn handler(ctx: Context<SetReadyState>, is_ready: bool) -> Result<()> {
     );
 
     let was_ready = player.is_ready;
+    ctx.accounts.game.last_activity = Clock::get()?.unix_timestamp;
 
     // Update the game's ready player count
     if is_ready && !was_ready {
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use crate::error::{SettlementError, SeasonError};
+use crate::state::{Game, LevelCurve, MatchRecord, MatchmakingConfig, Season};
+use crate::constants::{SETTLEMENT_TIMEOUT, CONFIG_SEED, LEVEL_CURVE_SEED, MATCH_RECORD_SEED, SEASON_SEED};
+
+/// Permissionless - decide the winner of an ended match, update ratings and season standings
+/// Remaining accounts: every rostered Player PDA and every player holding a
+/// reserved slot (writable) - players who left the running match are rated as a loss
+/// Runs once all results are reported, or after SETTLEMENT_TIMEOUT with whatever was reported
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, SettleMatch<'info>>) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+    let level_curve = &ctx.accounts.level_curve;
+    let clock = Clock::get()?;
+
+    require!(game.game_state == 2, SettlementError::GameNotEnded);
+    require!(!game.is_settled, SettlementError::AlreadySettled);
+
+    let ended_at = game.match_end_timestamp.unwrap_or(game.match_start_timestamp);
+    let all_reported = game.results_reported >= game.total_players();
+    let timed_out = clock.unix_timestamp.saturating_sub(ended_at) >= SETTLEMENT_TIMEOUT;
+    require!(all_reported || timed_out, SettlementError::ResultsPending);
+
+    let mut players = game.load_settlement_roster(ctx.remaining_accounts)?;
+
+    // Stat lines reported so far - created here if nobody reported a result
+    let match_record = &mut ctx.accounts.match_record;
+    match_record.init_if_new(game.key(), game);
+
+    // While a season is running its leaderboards must be updated too
+    let config = &ctx.accounts.config;
+    let mut season = if config.season_active {
+        let season = ctx.accounts.season.as_mut().ok_or(SeasonError::SeasonRequired)?;
+        require!(
+            season.is_active && season.season_id == config.current_season,
+            SeasonError::SeasonNotActive
+        );
+        Some(season)
+    } else {
+        None
+    };
+
+    // Higher team score wins, equal scores are a draw
+    let winning_team = match game.team_a_score.cmp(&game.team_b_score) {
+        std::cmp::Ordering::Greater => 1,
+        std::cmp::Ordering::Less => 2,
+        std::cmp::Ordering::Equal => 0,
+    };
+
+    let team_average = |team: u8| -> f64 {
+        let ratings: Vec<f64> = players.iter()
+            .filter(|p| game.settlement_team(&p.key()) == Some(team))
+            .map(|p| p.rating as f64)
+            .collect();
+        if ratings.is_empty() {
+            0.0
+        } else {
+            ratings.iter().sum::<f64>() / ratings.len() as f64
+        }
+    };
+    let team_a_rating = team_average(1);
+    let team_b_rating = team_average(2);
+
+    for player in players.iter_mut() {
+        let team = game.settlement_team(&player.key()).ok_or(SettlementError::PlayerNotInGame)?;
+        let departed = game.team_of(&player.key()).is_none();
+        let (own_rating, opponent_rating) = if team == 1 {
+            (team_a_rating, team_b_rating)
+        } else {
+            (team_b_rating, team_a_rating)
+        };
+        let won = !departed && winning_team == team;
+        let outcome = if departed {
+            0.0
+        } else if winning_team == 0 {
+            0.5
+        } else if won {
+            1.0
+        } else {
+            0.0
+        };
+
+        let old_rating = player.rating;
+        player.apply_rating_result(own_rating, opponent_rating, outcome);
+        msg!("Player {} rating {} -> {}", player.key(), old_rating, player.rating);
+
+        let player_key = player.key();
+        if won {
+            player.award_xp(player_key, level_curve.xp_per_win as u64, level_curve);
+        }
+
+        if let Some(season) = season.as_mut() {
+            let kills = match_record.players.iter()
+                .find(|line| line.player == player_key)
+                .map_or(0, |line| line.kills);
+
+            player.roll_season(season.season_id);
+            player.season_kills = player.season_kills.saturating_add(kills);
+            if won {
+                player.season_wins = player.season_wins.saturating_add(1);
+            }
+            season.record(player_key, player.rating, player.season_kills, player.season_wins);
+        }
+
+        // Release the player back to the menu, unless they already moved on to another game
+        player.total_matches_played = player.total_matches_played.saturating_add(1);
+        if player.current_game == Some(game.key()) {
+            player.current_game = None;
+            player.team = 0;
+            player.is_alive = false;
+            player.is_ready = false;
+        }
+
+        player.exit(&crate::ID)?;
+    }
+
+    game.winning_team = Some(winning_team);
+    game.is_settled = true;
+    game.last_activity = clock.unix_timestamp;
+
+    match_record.finalize(game);
+
+    msg!(
+        "Game {} settled - Team A {} vs Team B {}, winner: {}",
+        game.key(),
+        game.team_a_score,
+        game.team_b_score,
+        winning_team
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleMatch<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    #[account(
+        seeds = [LEVEL_CURVE_SEED.as_bytes()],
+        bump
+    )]
+    pub level_curve: Account<'info, LevelCurve>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = MatchRecord::SIZE,
+        seeds = [MATCH_RECORD_SEED.as_bytes(), game.key().as_ref()],
+        bump
+    )]
+    pub match_record: Account<'info, MatchRecord>,
+
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    /// Active season, required while config.season_active is set
+    #[account(
+        mut,
+        seeds = [SEASON_SEED.as_bytes(), &config.current_season.to_le_bytes()],
+        bump
+    )]
+    pub season: Option<Account<'info, Season>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,7 +1,11 @@
 
 use anchor_lang::prelude::*;
-use crate::error::StartGameError;
-use crate::constants::{PLAYER_SEED, MIN_PLAYERS_TO_START};
+use crate::error::{StartGameError, LobbyDirectoryError, MapError};
+use crate::state::LobbyDirectory;
+use crate::constants::{PLAYER_SEED, LOBBY_DIRECTORY_SEED, MIN_PLAYERS_TO_START};
+use map_registry::cpi::accounts::RecordMapPlay;
+use map_registry::program::MapRegistry;
+use map_registry::{MapMetadata, MAP_METADATA_SEED, MAP_PLAY_AUTHORITY_SEED};
 
 pub fn handler(ctx: Context<StartGame>) -> Result<()> {
     let game = &mut ctx.accounts.game;
@@ -20,6 +24,26 @@ pub fn handler(ctx: Context<StartGame>) -> Result<()> {
 
     game.game_state = 1;
     game.match_start_timestamp = clock.unix_timestamp;
+    game.last_activity = clock.unix_timestamp;
+
+    // Started lobbies can no longer be joined, take them out of the directory
+    if game.is_listed {
+        let lobby_directory = ctx.accounts.lobby_directory.as_mut()
+            .ok_or(LobbyDirectoryError::DirectoryPageRequired)?;
+        lobby_directory.remove(&game.key());
+        game.is_listed = false;
+    }
+
+    // Count the play on the map registry
+    let signer_seeds: &[&[&[u8]]] = &[&[MAP_PLAY_AUTHORITY_SEED, &[ctx.bumps.map_play_authority]]];
+    map_registry::cpi::record_map_play(CpiContext::new_with_signer(
+        ctx.accounts.map_registry_program.to_account_info(),
+        RecordMapPlay {
+            map_metadata: ctx.accounts.map_metadata.to_account_info(),
+            play_authority: ctx.accounts.map_play_authority.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
 
     Ok(())
 }
@@ -37,5 +61,32 @@ pub struct StartGame<'info> {
     )]
     pub player: AccountInfo<'info>,
 
+    /// Directory page the lobby is listed on, required while the lobby is listed
+    #[account(
+        mut,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &game.lobby_page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Option<Account<'info, LobbyDirectory>>,
+
+    /// Registry entry of the lobby's map, its play count is bumped
+    #[account(
+        mut,
+        seeds = [MAP_METADATA_SEED, game.map_id.as_bytes()],
+        bump,
+        seeds::program = map_registry::ID,
+        constraint = map_metadata.map_id == game.map_id @ MapError::WrongMap
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// CHECK: PDA signer for the map registry CPI, holds no data
+    #[account(
+        seeds = [MAP_PLAY_AUTHORITY_SEED],
+        bump
+    )]
+    pub map_play_authority: UncheckedAccount<'info>,
+
+    pub map_registry_program: Program<'info, MapRegistry>,
+
     pub authority: Signer<'info>,
 }
\ No newline at end of file
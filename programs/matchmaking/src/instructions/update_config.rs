@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::error::ConfigError;
+use crate::state::MatchmakingConfig;
+use crate::constants::{CONFIG_SEED, MIN_LOBBY_IDLE_TIMEOUT};
+
+/// Tune global matchmaking settings - admin only
+pub fn handler(ctx: Context<UpdateConfig>, lobby_idle_timeout: i64) -> Result<()> {
+    require!(
+        lobby_idle_timeout >= MIN_LOBBY_IDLE_TIMEOUT,
+        ConfigError::IdleTimeoutTooShort
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.lobby_idle_timeout = lobby_idle_timeout;
+
+    msg!("Lobby idle timeout set to {}s", lobby_idle_timeout);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump,
+        has_one = admin @ ConfigError::Unauthorized
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::error::{ConfigError, LevelCurveError};
+use crate::state::{LevelCurve, MatchmakingConfig};
+use crate::constants::{CONFIG_SEED, LEVEL_CURVE_SEED, MAX_LEVELS};
+
+/// Retune XP rewards and level thresholds
+/// Existing players keep their XP - levels are recomputed on their next XP award
+pub fn handler(
+    ctx: Context<UpdateLevelCurve>,
+    xp_per_kill: u32,
+    xp_per_score_point: u32,
+    xp_per_win: u32,
+    xp_per_match: u32,
+    level_thresholds: Vec<u64>,
+) -> Result<()> {
+    let level_curve = &mut ctx.accounts.level_curve;
+
+    require!(level_thresholds.len() < MAX_LEVELS, LevelCurveError::TooManyLevels);
+    require!(
+        level_thresholds.windows(2).all(|pair| pair[0] < pair[1]),
+        LevelCurveError::InvalidThresholds
+    );
+
+    level_curve.xp_per_kill = xp_per_kill;
+    level_curve.xp_per_score_point = xp_per_score_point;
+    level_curve.xp_per_win = xp_per_win;
+    level_curve.xp_per_match = xp_per_match;
+    level_curve.level_thresholds = level_thresholds;
+
+    msg!("Level curve updated - {} levels", level_curve.level_thresholds.len() + 1);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateLevelCurve<'info> {
+    #[account(
+        mut,
+        seeds = [LEVEL_CURVE_SEED.as_bytes()],
+        bump
+    )]
+    pub level_curve: Account<'info, LevelCurve>,
+
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump,
+        has_one = admin @ ConfigError::Unauthorized
+    )]
+    pub config: Account<'info, MatchmakingConfig>,
+
+    pub admin: Signer<'info>,
+}
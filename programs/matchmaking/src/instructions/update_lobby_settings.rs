@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::error::{LobbyDirectoryError, LobbySettingsError};
+use crate::state::{Game, LobbyDirectory};
+use crate::constants::LOBBY_DIRECTORY_SEED;
+use map_registry::{MapMetadata, MapRevision, MAP_METADATA_SEED, MAP_REVISION_SEED};
+
+/// Change the map, name and visibility of a waiting lobby - lobby owner only
+/// The lobby is pinned to the latest published revision of the new map
+/// The map's status is checked against the new visibility, so a lobby on an
+/// Unlisted map cannot go public
+pub fn handler(
+    ctx: Context<UpdateLobbySettings>,
+    map_id: String,
+    lobby_name: String,
+    is_private: bool,
+) -> Result<()> {
+    let game = &mut ctx.accounts.game;
+
+    require!(
+        ctx.accounts.authority.key() == game.created_by,
+        LobbySettingsError::NotLobbyOwner
+    );
+    require!(game.game_state == 0, LobbySettingsError::GameAlreadyStarted);
+    require!(
+        !lobby_name.is_empty() && lobby_name.len() <= 32,
+        LobbySettingsError::InvalidLobbyName
+    );
+
+    game.is_private = is_private;
+    game.set_map(map_id, &ctx.accounts.map_metadata, &ctx.accounts.map_revision)?;
+    game.lobby_name = lobby_name;
+    game.last_activity = Clock::get()?.unix_timestamp;
+
+    // Keep the server browser in sync - public lobbies are listed, private ones are not
+    if game.is_listed || !game.is_private {
+        let game_key = game.key();
+        let lobby_directory = ctx.accounts.lobby_directory.as_mut()
+            .ok_or(LobbyDirectoryError::DirectoryPageRequired)?;
+        if game.is_private {
+            lobby_directory.remove(&game_key);
+            game.is_listed = false;
+        } else {
+            lobby_directory.upsert(game_key, game)?;
+            game.is_listed = true;
+        }
+    }
+
+    msg!("Lobby {} now '{}' on map {}", game.key(), game.lobby_name, game.map_id);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(map_id: String)]
+pub struct UpdateLobbySettings<'info> {
+    #[account(mut)]
+    pub game: Account<'info, Game>,
+
+    /// Registry entry for the new map, proves the map exists
+    #[account(
+        seeds = [MAP_METADATA_SEED, map_id.as_bytes()],
+        bump,
+        seeds::program = map_registry::ID
+    )]
+    pub map_metadata: Account<'info, MapMetadata>,
+
+    /// Latest published revision of the map, the lobby is pinned to it
+    #[account(
+        seeds = [
+            MAP_REVISION_SEED,
+            map_id.as_bytes(),
+            &map_metadata.nonce.to_le_bytes(),
+            &map_metadata.published_version.to_le_bytes()
+        ],
+        bump,
+        seeds::program = map_registry::ID
+    )]
+    pub map_revision: Account<'info, MapRevision>,
+
+    /// Directory page the lobby is listed on, required while the lobby is listed or going public
+    #[account(
+        mut,
+        seeds = [LOBBY_DIRECTORY_SEED.as_bytes(), &game.lobby_page.to_le_bytes()],
+        bump
+    )]
+    pub lobby_directory: Option<Account<'info, LobbyDirectory>>,
+
+    pub authority: Signer<'info>,
+}
@@ -1,5 +1,6 @@
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod instructions;
 pub mod state;
 
@@ -7,6 +8,7 @@ use anchor_lang::prelude::*;
 
 pub use constants::*;
 pub use error::*;
+pub use events::*;
 pub use instructions::*;
 pub use state::*;
 
@@ -20,8 +22,8 @@ pub mod matchmaking {
         init_player::handler(ctx, args)
     }
 
-    pub fn init_game(ctx: Context<InitGame>, map_id: String) -> Result<()> {
-        init_game::handler(ctx, map_id)
+    pub fn init_game(ctx: Context<InitGame>, map_id: String, is_private: bool) -> Result<()> {
+        init_game::handler(ctx, map_id, is_private)
     }
 
     pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
@@ -43,4 +45,130 @@ pub mod matchmaking {
     pub fn set_ready_state(ctx: Context<SetReadyState>, is_ready: bool) -> Result<()> {
         set_ready_state::handler(ctx, is_ready)
     }
+
+    pub fn init_lobby_directory(ctx: Context<InitLobbyDirectory>, page: u32) -> Result<()> {
+        init_lobby_directory::handler(ctx, page)
+    }
+
+    pub fn init_match_queue(ctx: Context<InitMatchQueue>, mode: u8, region: u8) -> Result<()> {
+        init_match_queue::handler(ctx, mode, region)
+    }
+
+    pub fn enqueue(ctx: Context<Enqueue>, mode: u8, region: u8) -> Result<()> {
+        enqueue::handler(ctx, mode, region)
+    }
+
+    pub fn dequeue(ctx: Context<Dequeue>) -> Result<()> {
+        dequeue::handler(ctx)
+    }
+
+    pub fn match_queue<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MatchFromQueue<'info>>,
+        map_id: String,
+    ) -> Result<()> {
+        match_queue::handler(ctx, map_id)
+    }
+
+    pub fn fill_from_queue<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FillFromQueue<'info>>,
+    ) -> Result<()> {
+        fill_from_queue::handler(ctx)
+    }
+
+    pub fn end_game(ctx: Context<EndGame>) -> Result<()> {
+        end_game::handler(ctx)
+    }
+
+    pub fn record_player_result(
+        ctx: Context<RecordPlayerResult>,
+        kills: u32,
+        deaths: u32,
+        score: u32,
+    ) -> Result<()> {
+        record_player_result::handler(ctx, kills, deaths, score)
+    }
+
+    pub fn settle_match<'info>(ctx: Context<'_, '_, 'info, 'info, SettleMatch<'info>>) -> Result<()> {
+        settle_match::handler(ctx)
+    }
+
+    pub fn balance_teams<'info>(ctx: Context<'_, '_, 'info, 'info, BalanceTeams<'info>>) -> Result<()> {
+        balance_teams::handler(ctx)
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        initialize_config::handler(ctx)
+    }
+
+    pub fn init_level_curve(ctx: Context<InitLevelCurve>) -> Result<()> {
+        init_level_curve::handler(ctx)
+    }
+
+    pub fn update_level_curve(
+        ctx: Context<UpdateLevelCurve>,
+        xp_per_kill: u32,
+        xp_per_score_point: u32,
+        xp_per_win: u32,
+        xp_per_match: u32,
+        level_thresholds: Vec<u64>,
+    ) -> Result<()> {
+        update_level_curve::handler(ctx, xp_per_kill, xp_per_score_point, xp_per_win, xp_per_match, level_thresholds)
+    }
+
+    pub fn open_season(ctx: Context<OpenSeason>) -> Result<()> {
+        open_season::handler(ctx)
+    }
+
+    pub fn close_season(ctx: Context<CloseSeason>) -> Result<()> {
+        close_season::handler(ctx)
+    }
+
+    pub fn pause_match(ctx: Context<PauseMatch>) -> Result<()> {
+        pause_match::handler(ctx)
+    }
+
+    pub fn resume_match(ctx: Context<ResumeMatch>) -> Result<()> {
+        resume_match::handler(ctx)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, lobby_idle_timeout: i64) -> Result<()> {
+        update_config::handler(ctx, lobby_idle_timeout)
+    }
+
+    pub fn close_stale_lobby(ctx: Context<CloseStaleLobby>) -> Result<()> {
+        close_stale_lobby::handler(ctx)
+    }
+
+    pub fn force_leave_stale_game(ctx: Context<ForceLeaveStaleGame>) -> Result<()> {
+        force_leave_stale_game::handler(ctx)
+    }
+
+    pub fn rejoin_game(ctx: Context<RejoinGame>) -> Result<()> {
+        rejoin_game::handler(ctx)
+    }
+
+    pub fn set_idle_timeout(ctx: Context<SetIdleTimeout>, idle_timeout: i64) -> Result<()> {
+        set_idle_timeout::handler(ctx, idle_timeout)
+    }
+
+    pub fn remove_idle_player(ctx: Context<RemoveIdlePlayer>) -> Result<()> {
+        remove_idle_player::handler(ctx)
+    }
+
+    pub fn rename_player(ctx: Context<RenamePlayer>, new_username: String) -> Result<()> {
+        rename_player::handler(ctx, new_username)
+    }
+
+    pub fn close_player(ctx: Context<ClosePlayer>) -> Result<()> {
+        close_player::handler(ctx)
+    }
+
+    pub fn update_lobby_settings(
+        ctx: Context<UpdateLobbySettings>,
+        map_id: String,
+        lobby_name: String,
+        is_private: bool,
+    ) -> Result<()> {
+        update_lobby_settings::handler(ctx, map_id, lobby_name, is_private)
+    }
 }
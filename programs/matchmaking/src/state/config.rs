@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+/// Global matchmaking settings, one per program
+#[account]
+pub struct MatchmakingConfig {
+    pub admin: Pubkey, // Can tune the level curve and other global settings
+
+    // Ranked seasons
+    pub current_season: u32, // Id of the latest season, 0 before the first one opens
+    pub season_active: bool,
+
+    // Seconds without activity before a lobby can be closed by anyone
+    pub lobby_idle_timeout: i64,
+}
+
+impl MatchmakingConfig {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // admin
+        4 + 1 + // current_season + season_active
+        8; // lobby_idle_timeout
+}
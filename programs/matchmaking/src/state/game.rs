@@ -1,4 +1,21 @@
 use anchor_lang::prelude::*;
+use crate::constants::{DEFAULT_IDLE_TIMEOUT, MAX_PAUSE_DURATION, MAX_PLAYERS_PER_TEAM, MAX_TOTAL_PLAYERS};
+use crate::error::{JoinGameError, LeaveGameError, LobbyDirectoryError, MapError, RosterError};
+use map_registry::{MapMetadata, MapRevision, MapStatus};
+use crate::state::{LobbyDirectory, Player};
+
+/// Team slot held for a player who dropped out of a running match
+/// Kept until settlement even once expired, so the leaver is still rated
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ReservedSlot {
+    pub player: Pubkey,  // Player PDA
+    pub team: u8,        // 1=A, 2=B
+    pub expires_at: i64, // Rejoin deadline
+}
+
+impl ReservedSlot {
+    pub const SIZE: usize = 32 + 1 + 8;
+}
 
 #[account]
 pub struct Game {
@@ -16,6 +33,8 @@ pub struct Game {
     pub winning_team: Option<u8>,   // Winning team (0=draw, 1=team_a, 2=team_b)
     pub match_type: u8,             // Match type (1=team_deathmatch) for now
     pub map_id: String,             // Map ID reference
+    pub map_version: u32,           // Published map revision the match is played on
+    pub map_content_hash: [u8; 32], // Hash of that revision's objects, for client cache checks
 
     // NEW: Lobby features
     pub lobby_name: String,
@@ -27,4 +46,352 @@ pub struct Game {
     // Player tracking - Vec of Player PDAs
     pub team_a_players: Vec<Pubkey>,  // Max 5 players
     pub team_b_players: Vec<Pubkey>,  // Max 5 players
-}
\ No newline at end of file
+
+    // Lobby directory listing
+    pub lobby_page: u32,    // Directory page the lobby is listed on
+    pub is_listed: bool,    // True while the lobby has an entry in the directory
+
+    // Match settlement
+    pub results_reported: u8, // Players whose GamePlayer stats have been reported
+    pub is_settled: bool,     // Winner decided and ratings applied
+
+    // Pause tracking - paused time does not count toward match_duration
+    pub paused_at: i64,          // When the current pause started (0 if not paused)
+    pub total_paused_time: i64,  // Seconds spent paused in completed pauses
+
+    // Last join/leave/ready/start, used to expire abandoned lobbies
+    pub last_activity: i64,
+
+    // Slots held for players who left mid-match and may reconnect
+    pub reserved_slots: Vec<ReservedSlot>,
+
+    // AFK detection
+    pub idle_timeout: i64, // Seconds without input before a player can be kicked
+    pub resumed_at: i64,   // Last resume, idle time before it does not count
+}
+
+impl Game {
+    pub const SIZE: usize = 8 + // discriminator
+        4 + 4 + 4 + 4 + 4 + // team scores, team kills, match_duration
+        8 + (1 + 8) + // match_start_timestamp + match_end_timestamp Option
+        1 + 1 + 1 + 1 + // game_state, max_players_per_team, team counts
+        (1 + 1) + 1 + // winning_team Option + match_type
+        (4 + 50) + 4 + 32 + // map_id String (4 byte length + up to 50 chars) + map_version + map_content_hash
+        (4 + 32) + // lobby_name string with length prefix
+        32 + // created_by pubkey
+        1 + 1 + 1 + // is_private + ready_players + map_selection
+        (4 + 32 * 5) + (4 + 32 * 5) + // team_a_players Vec (4 byte length + max 5 pubkeys) + team_b_players Vec
+        4 + 1 + // lobby_page + is_listed
+        1 + 1 + // results_reported + is_settled
+        8 + 8 + // paused_at + total_paused_time
+        8 + // last_activity
+        (4 + ReservedSlot::SIZE * MAX_TOTAL_PLAYERS as usize) + // reserved_slots Vec
+        8 + 8; // idle_timeout + resumed_at
+
+    /// Pin the lobby to a published map revision so every participant loads the same geometry
+    /// Public lobbies need a Published map, private ones may also use Unlisted maps
+    pub fn set_map(&mut self, map_id: String, map: &MapMetadata, revision: &MapRevision) -> Result<()> {
+        match map.status {
+            MapStatus::Published => {}
+            MapStatus::Unlisted => require!(self.is_private, MapError::MapNotPublic),
+            MapStatus::Draft | MapStatus::Delisted => return err!(MapError::MapNotPlayable),
+        }
+        require!(revision.is_complete(), MapError::RevisionIncomplete);
+        require!(revision.has_team_spawns(), MapError::MissingTeamSpawns);
+
+        self.map_id = map_id;
+        self.map_version = revision.version;
+        self.map_content_hash = revision.content_hash;
+        Ok(())
+    }
+
+    /// Reset every field to a fresh waiting lobby owned by `created_by`
+    /// The map is set separately with set_map
+    pub fn init_lobby(&mut self, created_by: Pubkey, timestamp: i64) {
+        self.match_start_timestamp = timestamp;
+        self.match_end_timestamp = None;
+        self.game_state = 0; // waiting state
+        self.team_a_score = 0;
+        self.team_b_score = 0;
+        self.team_a_kills = 0;
+        self.team_b_kills = 0;
+        self.current_players_team_a = 0;
+        self.current_players_team_b = 0;
+        self.match_duration = 300; // 5 minutes
+        self.max_players_per_team = MAX_PLAYERS_PER_TEAM;
+        self.winning_team = None;
+        self.match_type = 1; // team deathmatch
+
+        // Lobby features
+        self.lobby_name = "New Game Room".to_string();
+        self.created_by = created_by;
+        self.is_private = false;
+        self.ready_players = 0;
+        self.map_selection = 0;
+
+        // Player tracking vectors
+        self.team_a_players = Vec::new();
+        self.team_b_players = Vec::new();
+
+        self.lobby_page = 0;
+        self.is_listed = false;
+
+        self.results_reported = 0;
+        self.is_settled = false;
+
+        self.paused_at = 0;
+        self.total_paused_time = 0;
+
+        self.last_activity = timestamp;
+
+        self.reserved_slots = Vec::new();
+
+        self.idle_timeout = DEFAULT_IDLE_TIMEOUT;
+        self.resumed_at = 0;
+    }
+
+    /// A waiting lobby, or a settled match, nobody has touched within the idle timeout
+    pub fn is_stale(&self, now: i64, idle_timeout: i64) -> bool {
+        let idle = now.saturating_sub(self.last_activity) >= idle_timeout;
+        idle && (self.game_state == 0 || (self.game_state == 2 && self.is_settled))
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.game_state == 3
+    }
+
+    /// Paused for longer than MAX_PAUSE_DURATION - the owner may have abandoned it
+    pub fn pause_expired(&self, now: i64) -> bool {
+        self.is_paused() && now.saturating_sub(self.paused_at) >= MAX_PAUSE_DURATION
+    }
+
+    /// A running or paused match anyone may end: it has played its full
+    /// duration or its pause has expired
+    pub fn is_overdue(&self, now: i64) -> bool {
+        (self.game_state == 1 || self.is_paused())
+            && (self.active_elapsed(now) >= self.match_duration as i64 || self.pause_expired(now))
+    }
+
+    /// Move a running or paused match to ended, folding any open pause into total_paused_time
+    pub fn finish(&mut self, now: i64) {
+        if self.is_paused() {
+            self.total_paused_time += now.saturating_sub(self.paused_at);
+            self.paused_at = 0;
+        }
+        self.game_state = 2;
+        self.match_end_timestamp = Some(now);
+        self.last_activity = now;
+    }
+
+    /// Seconds of unpaused play since the match started
+    pub fn active_elapsed(&self, now: i64) -> i64 {
+        let current_pause = if self.is_paused() { now.saturating_sub(self.paused_at) } else { 0 };
+        now.saturating_sub(self.match_start_timestamp)
+            .saturating_sub(self.total_paused_time)
+            .saturating_sub(current_pause)
+    }
+
+    /// True once a player whose last input was at `last_update` has been idle past the threshold
+    /// Time before the match started or last resumed is not held against them
+    pub fn is_player_idle(&self, last_update: i64, now: i64) -> bool {
+        let since = last_update.max(self.match_start_timestamp).max(self.resumed_at);
+        self.game_state == 1 && now.saturating_sub(since) >= self.idle_timeout
+    }
+
+    pub fn total_players(&self) -> u8 {
+        self.current_players_team_a + self.current_players_team_b
+    }
+
+    /// Team the Player PDA is rostered on (1=A, 2=B), None if not in this game
+    pub fn team_of(&self, player_key: &Pubkey) -> Option<u8> {
+        if self.team_a_players.contains(player_key) {
+            Some(1)
+        } else if self.team_b_players.contains(player_key) {
+            Some(2)
+        } else {
+            None
+        }
+    }
+
+    /// Add a Player PDA to the smaller team, returns the team it joined (1=A, 2=B)
+    pub fn add_player(&mut self, player_key: Pubkey) -> Result<u8> {
+        // Check if game has space
+        let total_players = self.current_players_team_a + self.current_players_team_b;
+        require!(total_players < MAX_TOTAL_PLAYERS, JoinGameError::GameFull);
+
+        // Determine which team to join (balance teams)
+        let team = if self.current_players_team_a <= self.current_players_team_b { 1 } else { 2 };
+        self.add_player_to_team(player_key, team)?;
+
+        Ok(team)
+    }
+
+    /// Add a Player PDA to a specific team vector
+    pub fn add_player_to_team(&mut self, player_key: Pubkey, team: u8) -> Result<()> {
+        if team == 1 {
+            require!(
+                self.team_a_players.len() < MAX_PLAYERS_PER_TEAM as usize,
+                JoinGameError::TeamFull
+            );
+            self.team_a_players.push(player_key);
+            self.current_players_team_a += 1;
+        } else {
+            require!(
+                self.team_b_players.len() < MAX_PLAYERS_PER_TEAM as usize,
+                JoinGameError::TeamFull
+            );
+            self.team_b_players.push(player_key);
+            self.current_players_team_b += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Drop a player from whichever team vector holds them
+    pub fn remove_player(&mut self, player_key: &Pubkey) {
+        if let Some(pos) = self.team_a_players.iter().position(|k| k == player_key) {
+            self.team_a_players.remove(pos);
+            self.current_players_team_a = self.current_players_team_a.saturating_sub(1);
+        } else if let Some(pos) = self.team_b_players.iter().position(|k| k == player_key) {
+            self.team_b_players.remove(pos);
+            self.current_players_team_b = self.current_players_team_b.saturating_sub(1);
+        }
+    }
+
+    /// Public waiting lobbies with a free slot belong in the directory
+    pub fn wants_listing(&self) -> bool {
+        self.game_state == 0 && !self.is_private && self.total_players() < MAX_TOTAL_PLAYERS
+    }
+
+    /// List, refresh or unlist the lobby's directory row to match wants_listing
+    /// The directory page is required whenever the row has to change
+    pub fn sync_listing(
+        &mut self,
+        game_key: Pubkey,
+        lobby_directory: Option<&mut LobbyDirectory>,
+    ) -> Result<()> {
+        let wants_listing = self.wants_listing();
+        if !self.is_listed && !wants_listing {
+            return Ok(());
+        }
+
+        let lobby_directory = lobby_directory.ok_or(LobbyDirectoryError::DirectoryPageRequired)?;
+        if wants_listing {
+            lobby_directory.upsert(game_key, self)?;
+        } else {
+            lobby_directory.remove(&game_key);
+        }
+        self.is_listed = wants_listing;
+        Ok(())
+    }
+
+    /// Bookkeeping once a player has been taken off the roster: sync the
+    /// directory row and hand ownership to the first remaining player if the
+    /// owner left. `next_owner` is that player's account, ownership goes to
+    /// their wallet. Returns true when the owner left an empty lobby, which
+    /// the caller should then close.
+    pub fn after_player_left(
+        &mut self,
+        game_key: Pubkey,
+        owner_left: bool,
+        lobby_directory: Option<&mut LobbyDirectory>,
+        next_owner: Option<&Account<Player>>,
+    ) -> Result<bool> {
+        let total_players = self.total_players();
+
+        // Keep the lobby directory in sync - drop the entry if the room is about to close
+        if owner_left && total_players == 0 {
+            if self.is_listed {
+                lobby_directory.ok_or(LobbyDirectoryError::DirectoryPageRequired)?.remove(&game_key);
+                self.is_listed = false;
+            }
+        } else {
+            self.sync_listing(game_key, lobby_directory)?;
+        }
+
+        // If there are other players remaining, transfer ownership to the first remaining player
+        if owner_left && total_players > 0 {
+            let expected = if !self.team_a_players.is_empty() {
+                self.team_a_players[0]
+            } else {
+                self.team_b_players[0]
+            };
+            let next_owner = next_owner.ok_or(LeaveGameError::NextOwnerRequired)?;
+            require_keys_eq!(next_owner.key(), expected, LeaveGameError::WrongNextOwner);
+
+            let new_owner = next_owner.authority;
+            self.created_by = new_owner;
+            msg!("Owner left. Ownership transferred to: {}", new_owner);
+        }
+
+        Ok(owner_left && total_players == 0)
+    }
+
+    /// Hold the player's team slot until `expires_at`, replacing any older reservation
+    pub fn reserve_slot(&mut self, player_key: Pubkey, team: u8, expires_at: i64) {
+        self.reserved_slots.retain(|s| s.player != player_key);
+        self.reserved_slots.push(ReservedSlot { player: player_key, team, expires_at });
+    }
+
+    /// Remove and return the player's reservation, if any
+    pub fn take_reserved_slot(&mut self, player_key: &Pubkey) -> Option<ReservedSlot> {
+        let pos = self.reserved_slots.iter().position(|s| &s.player == player_key)?;
+        Some(self.reserved_slots.remove(pos))
+    }
+
+    /// Team a player who left the running match was on, None if they hold no reserved slot
+    pub fn departed_team(&self, player_key: &Pubkey) -> Option<u8> {
+        self.reserved_slots.iter().find(|s| &s.player == player_key).map(|s| s.team)
+    }
+
+    /// Team the player is rated with at settlement - rostered or departed
+    pub fn settlement_team(&self, player_key: &Pubkey) -> Option<u8> {
+        self.team_of(player_key).or_else(|| self.departed_team(player_key))
+    }
+
+    /// Load the writable Player accounts for every rostered player
+    /// Each team member must appear exactly once, in any order
+    pub fn load_roster<'info>(
+        &self,
+        player_infos: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<Account<'info, Player>>> {
+        self.load_players(player_infos, false)
+    }
+
+    /// Like load_roster, but players holding a reserved slot must be passed too
+    pub fn load_settlement_roster<'info>(
+        &self,
+        player_infos: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<Account<'info, Player>>> {
+        self.load_players(player_infos, true)
+    }
+
+    fn load_players<'info>(
+        &self,
+        player_infos: &'info [AccountInfo<'info>],
+        include_departed: bool,
+    ) -> Result<Vec<Account<'info, Player>>> {
+        let departed = if include_departed { self.reserved_slots.len() } else { 0 };
+        require!(
+            player_infos.len() == self.total_players() as usize + departed,
+            RosterError::RosterMismatch
+        );
+
+        let mut players: Vec<Account<'info, Player>> = Vec::with_capacity(player_infos.len());
+        for player_info in player_infos {
+            let player = Account::<Player>::try_from(player_info)?;
+            let team = if include_departed {
+                self.settlement_team(&player.key())
+            } else {
+                self.team_of(&player.key())
+            };
+            require!(
+                team.is_some() && !players.iter().any(|p| p.key() == player.key()),
+                RosterError::RosterMismatch
+            );
+            players.push(player);
+        }
+
+        Ok(players)
+    }
+}
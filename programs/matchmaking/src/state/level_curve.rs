@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_LEVELS;
+
+/// XP rewards and level thresholds, tunable by the admin without migrating Player accounts
+#[account]
+pub struct LevelCurve {
+    pub xp_per_kill: u32,
+    pub xp_per_score_point: u32,
+    pub xp_per_win: u32,
+    pub xp_per_match: u32,          // Participation reward for finishing a match
+
+    // Total XP required to reach level 2, 3, ... - strictly increasing
+    pub level_thresholds: Vec<u64>, // Max MAX_LEVELS entries
+}
+
+impl LevelCurve {
+    pub const SIZE: usize = 8 + // discriminator
+        4 + 4 + 4 + 4 + // xp rewards
+        4 + 8 * MAX_LEVELS; // level_thresholds Vec
+
+    /// Level reached with the given total XP (levels start at 1)
+    pub fn level_for(&self, xp: u64) -> u32 {
+        1 + self.level_thresholds.iter().take_while(|&&threshold| xp >= threshold).count() as u32
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_LOBBIES_PER_PAGE;
+use crate::error::LobbyDirectoryError;
+use crate::state::Game;
+
+/// Summary of a public waiting lobby, enough to render a server browser row
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LobbyEntry {
+    pub game: Pubkey,
+    pub map_id: String,              // Max 50 chars
+    pub lobby_name: String,          // Max 32 chars
+    pub current_players_team_a: u8,
+    pub current_players_team_b: u8,
+    pub max_players_per_team: u8,
+    pub created_at: i64,
+}
+
+impl LobbyEntry {
+    pub const SIZE: usize = 32 + // game
+        (4 + 50) + // map_id
+        (4 + 32) + // lobby_name
+        1 + 1 + 1 + // team counts + max_players_per_team
+        8; // created_at
+
+    pub fn from_game(game_key: Pubkey, game: &Game) -> Self {
+        Self {
+            game: game_key,
+            map_id: game.map_id.clone(),
+            lobby_name: game.lobby_name.clone(),
+            current_players_team_a: game.current_players_team_a,
+            current_players_team_b: game.current_players_team_b,
+            max_players_per_team: game.max_players_per_team,
+            created_at: game.match_start_timestamp,
+        }
+    }
+}
+
+/// One page of the global lobby directory
+/// Pages are PDAs derived from [LOBBY_DIRECTORY_SEED, page] so clients can walk them in order
+#[account]
+pub struct LobbyDirectory {
+    pub page: u32,
+    pub entries: Vec<LobbyEntry>, // Max MAX_LOBBIES_PER_PAGE entries
+}
+
+impl LobbyDirectory {
+    pub const SIZE: usize = 8 + // discriminator
+        4 + // page
+        4 + LobbyEntry::SIZE * MAX_LOBBIES_PER_PAGE; // entries Vec
+
+    /// Insert the lobby, or refresh its summary if it is already listed
+    pub fn upsert(&mut self, game_key: Pubkey, game: &Game) -> Result<()> {
+        let entry = LobbyEntry::from_game(game_key, game);
+
+        if let Some(existing) = self.entries.iter_mut().find(|e| e.game == game_key) {
+            *existing = entry;
+            return Ok(());
+        }
+
+        require!(
+            self.entries.len() < MAX_LOBBIES_PER_PAGE,
+            LobbyDirectoryError::DirectoryPageFull
+        );
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    /// Drop the lobby from this page, returns true if it was listed here
+    pub fn remove(&mut self, game_key: &Pubkey) -> bool {
+        if let Some(pos) = self.entries.iter().position(|e| &e.game == game_key) {
+            self.entries.remove(pos);
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_MATCH_HISTORY;
+
+/// Personal index of the matches a player has finished, for profile pages
+/// PDA derived from [MATCH_HISTORY_SEED, authority]
+#[account]
+pub struct PlayerMatchHistory {
+    /// The wallet this history belongs to
+    pub owner: Pubkey,
+
+    /// Number of matches ever recorded, including ones rotated out of the list
+    pub match_count: u32,
+
+    /// MatchRecord PDAs, oldest first - keeps the most recent MAX_MATCH_HISTORY
+    pub match_records: Vec<Pubkey>,
+}
+
+impl PlayerMatchHistory {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + 4 + // owner + match_count
+        4 + 32 * MAX_MATCH_HISTORY; // match_records Vec
+
+    pub fn push(&mut self, owner: Pubkey, match_record: Pubkey) {
+        self.owner = owner;
+
+        if self.match_records.contains(&match_record) {
+            return;
+        }
+        if self.match_records.len() >= MAX_MATCH_HISTORY {
+            self.match_records.remove(0);
+        }
+        self.match_records.push(match_record);
+        self.match_count = self.match_count.saturating_add(1);
+    }
+}
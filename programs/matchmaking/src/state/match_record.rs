@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use crate::constants::MAX_TOTAL_PLAYERS;
+use crate::state::Game;
+
+/// Final stat line of one player in a match
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PlayerStatLine {
+    pub player: Pubkey,    // Player PDA
+    pub authority: Pubkey, // Player wallet
+    pub team: u8,          // 1 = Team A, 2 = Team B
+    pub kills: u32,
+    pub deaths: u32,
+    pub score: u32,
+}
+
+impl PlayerStatLine {
+    pub const SIZE: usize = 32 + 32 + 1 + 4 + 4 + 4;
+}
+
+/// Durable result of a match, outlives the Game account
+/// PDA derived from [MATCH_RECORD_SEED, game]
+#[account]
+pub struct MatchRecord {
+    pub game: Pubkey,
+    pub map_id: String,             // Max 50 chars
+    pub map_version: u32,           // Map revision the match was played on
+    pub match_type: u8,             // Match type (1=team_deathmatch)
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub team_a_score: u32,
+    pub team_b_score: u32,
+    pub team_a_kills: u32,
+    pub team_b_kills: u32,
+    pub winning_team: Option<u8>,   // Set at settlement (0=draw, 1=team_a, 2=team_b)
+    pub players: Vec<PlayerStatLine>, // Max MAX_TOTAL_PLAYERS entries
+}
+
+impl MatchRecord {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // game
+        (4 + 50) + 4 + // map_id + map_version
+        1 + 8 + 8 + // match_type + started_at + ended_at
+        4 + 4 + 4 + 4 + // team scores + team kills
+        (1 + 1) + // winning_team Option
+        4 + PlayerStatLine::SIZE * MAX_TOTAL_PLAYERS as usize; // players Vec
+
+    /// Fill the header the first time the record is touched
+    pub fn init_if_new(&mut self, game_key: Pubkey, game: &Game) {
+        if self.game != Pubkey::default() {
+            return;
+        }
+
+        self.game = game_key;
+        self.map_id = game.map_id.clone();
+        self.map_version = game.map_version;
+        self.match_type = game.match_type;
+        self.started_at = game.match_start_timestamp;
+        self.ended_at = game.match_end_timestamp.unwrap_or(game.match_start_timestamp);
+        self.winning_team = None;
+        self.players = Vec::new();
+    }
+
+    /// Copy the final team totals and winner from the settled game
+    pub fn finalize(&mut self, game: &Game) {
+        self.team_a_score = game.team_a_score;
+        self.team_b_score = game.team_b_score;
+        self.team_a_kills = game.team_a_kills;
+        self.team_b_kills = game.team_b_kills;
+        self.winning_team = game.winning_team;
+    }
+}
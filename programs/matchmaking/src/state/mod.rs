@@ -1,5 +1,21 @@
 pub mod game;
 pub mod player;
+pub mod lobby_directory;
+pub mod queue;
+pub mod config;
+pub mod level_curve;
+pub mod match_record;
+pub mod match_history;
+pub mod season;
+pub mod username;
 
 pub use game::*;
-pub use player::*;
\ No newline at end of file
+pub use player::*;
+pub use lobby_directory::*;
+pub use queue::*;
+pub use config::*;
+pub use level_curve::*;
+pub use match_record::*;
+pub use match_history::*;
+pub use season::*;
+pub use username::*;
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use crate::events::PlayerLeveledUp;
+use crate::state::LevelCurve;
+use crate::constants::{QUEUE_ENTRY_TIMEOUT, RATING_K_FACTOR, PROVISIONAL_K_FACTOR, PROVISIONAL_MATCHES};
 
 #[account]
 pub struct Player {
@@ -27,4 +30,72 @@ pub struct Player {
     pub rotation_x: f32,  // pitch
     pub rotation_y: f32,  // yaw
     pub rotation_z: f32,  // roll
-}
\ No newline at end of file
+
+    // Matchmaking queue
+    pub current_queue: Option<Pubkey>, // PDA of the queue the player is waiting in
+    pub queued_at: i64,
+
+    // Skill rating (Elo), updated at match settlement
+    pub rating: u32,
+
+    // Experience, level is derived from it through the LevelCurve
+    pub xp: u64,
+
+    // Stats for the season in season_id, reset when a new season's first match settles
+    pub season_id: u32,
+    pub season_kills: u32,
+    pub season_wins: u32,
+
+    // Last rename_player, enforces the rename cooldown
+    pub last_rename_timestamp: i64,
+}
+
+impl Player {
+    /// True while the player holds a queue entry that has not timed out
+    pub fn is_queued(&self, now: i64) -> bool {
+        self.current_queue.is_some() && now.saturating_sub(self.queued_at) < QUEUE_ENTRY_TIMEOUT
+    }
+
+    /// Apply an Elo update against the opposing team's average rating
+    /// outcome is 1.0 for a win, 0.5 for a draw and 0.0 for a loss
+    pub fn apply_rating_result(&mut self, team_rating: f64, opponent_rating: f64, outcome: f64) {
+        let expected = 1.0 / (1.0 + 10f64.powf((opponent_rating - team_rating) / 400.0));
+        let k_factor = if self.total_matches_played < PROVISIONAL_MATCHES {
+            PROVISIONAL_K_FACTOR
+        } else {
+            RATING_K_FACTOR
+        };
+
+        let delta = (k_factor * (outcome - expected)).round() as i64;
+        self.rating = (self.rating as i64 + delta).clamp(0, u32::MAX as i64) as u32;
+    }
+
+    /// Add XP and recompute the level, emitting PlayerLeveledUp when it goes up
+    /// Levels never go down, even if the curve is retuned later
+    pub fn award_xp(&mut self, player_key: Pubkey, amount: u64, curve: &LevelCurve) {
+        self.xp = self.xp.saturating_add(amount);
+
+        let new_level = curve.level_for(self.xp);
+        if new_level > self.level {
+            let old_level = self.level;
+            self.level = new_level;
+
+            emit!(PlayerLeveledUp {
+                player: player_key,
+                authority: self.authority,
+                old_level,
+                new_level,
+                xp: self.xp,
+            });
+        }
+    }
+
+    /// Start counting season stats from zero if the player last played an older season
+    pub fn roll_season(&mut self, season_id: u32) {
+        if self.season_id != season_id {
+            self.season_id = season_id;
+            self.season_kills = 0;
+            self.season_wins = 0;
+        }
+    }
+}
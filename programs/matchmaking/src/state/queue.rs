@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::constants::{MAX_QUEUE_ENTRIES, QUEUE_ENTRY_TIMEOUT};
+use crate::error::QueueError;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct QueueEntry {
+    pub player: Pubkey,   // Player PDA waiting for a match
+    pub enqueued_at: i64,
+}
+
+impl QueueEntry {
+    pub const SIZE: usize = 32 + 8;
+
+    pub fn is_stale(&self, now: i64) -> bool {
+        now.saturating_sub(self.enqueued_at) >= QUEUE_ENTRY_TIMEOUT
+    }
+}
+
+/// Players waiting for a match, one queue per (mode, region)
+#[account]
+pub struct MatchQueue {
+    pub mode: u8,             // Match type the queue fills (1=team_deathmatch)
+    pub region: u8,
+    pub games_created: u32,   // Counter for unique queue Game PDA derivation
+    pub entries: Vec<QueueEntry>, // Max MAX_QUEUE_ENTRIES entries, oldest first
+}
+
+impl MatchQueue {
+    pub const SIZE: usize = 8 + // discriminator
+        1 + 1 + 4 + // mode + region + games_created
+        4 + QueueEntry::SIZE * MAX_QUEUE_ENTRIES; // entries Vec
+
+    /// Drop entries that have waited longer than QUEUE_ENTRY_TIMEOUT
+    pub fn prune_stale(&mut self, now: i64) {
+        self.entries.retain(|e| !e.is_stale(now));
+    }
+
+    pub fn push(&mut self, player: Pubkey, now: i64) -> Result<()> {
+        require!(self.entries.len() < MAX_QUEUE_ENTRIES, QueueError::QueueFull);
+        self.entries.push(QueueEntry { player, enqueued_at: now });
+        Ok(())
+    }
+
+    /// Remove the player's entry, returns it if the player was queued here
+    pub fn take(&mut self, player: &Pubkey) -> Option<QueueEntry> {
+        let pos = self.entries.iter().position(|e| &e.player == player)?;
+        Some(self.entries.remove(pos))
+    }
+}
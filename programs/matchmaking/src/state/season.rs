@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::constants::LEADERBOARD_SIZE;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeaderboardEntry {
+    pub player: Pubkey, // Player PDA
+    pub value: u64,
+}
+
+impl LeaderboardEntry {
+    pub const SIZE: usize = 32 + 8;
+}
+
+/// A ranked season - top-N standings by rating, kills and wins
+/// PDA derived from [SEASON_SEED, season_id], left untouched once closed as the archive
+#[account]
+pub struct Season {
+    pub season_id: u32,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    pub is_active: bool,
+
+    // Sorted highest first, max LEADERBOARD_SIZE entries each
+    pub top_rating: Vec<LeaderboardEntry>,
+    pub top_kills: Vec<LeaderboardEntry>,
+    pub top_wins: Vec<LeaderboardEntry>,
+}
+
+impl Season {
+    pub const SIZE: usize = 8 + // discriminator
+        4 + 8 + (1 + 8) + 1 + // season_id, started_at, ended_at Option, is_active
+        (4 + LeaderboardEntry::SIZE * LEADERBOARD_SIZE) * 3; // top_rating, top_kills, top_wins
+
+    /// Refresh a player's standing on all three leaderboards
+    pub fn record(&mut self, player: Pubkey, rating: u32, kills: u32, wins: u32) {
+        Self::update_board(&mut self.top_rating, player, rating as u64);
+        Self::update_board(&mut self.top_kills, player, kills as u64);
+        Self::update_board(&mut self.top_wins, player, wins as u64);
+    }
+
+    fn update_board(board: &mut Vec<LeaderboardEntry>, player: Pubkey, value: u64) {
+        board.retain(|entry| entry.player != player);
+
+        let pos = board.iter().position(|entry| entry.value < value).unwrap_or(board.len());
+        if pos < LEADERBOARD_SIZE {
+            board.insert(pos, LeaderboardEntry { player, value });
+            board.truncate(LEADERBOARD_SIZE);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::error::InitPlayerError;
+
+/// Claim on a username, guarantees no two players share one
+/// PDA derived from [USERNAME_SEED, normalized username]
+#[account]
+pub struct UsernameRecord {
+    /// Wallet of the player holding the name
+    pub owner: Pubkey,
+
+    /// The name as the player typed it
+    pub username: String,
+}
+
+impl UsernameRecord {
+    pub const SIZE: usize = 8 + // discriminator
+        32 + // owner
+        (4 + 32); // username string
+}
+
+/// Case-folded form used for the PDA seed, so "Alice" and "alice" collide
+pub fn normalize_username(username: &str) -> Vec<u8> {
+    username.to_ascii_lowercase().into_bytes()
+}
+
+/// 3-32 ASCII letters, digits or underscores - keeps case folding unambiguous
+pub fn validate_username(username: &str) -> Result<()> {
+    require!(
+        username.len() >= 3 && username.len() <= 32,
+        InitPlayerError::InvalidUsernameLength
+    );
+    require!(
+        username.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_'),
+        InitPlayerError::InvalidUsername
+    );
+    Ok(())
+}
+
+/// Username from init_player args (length-prefixed bytes)
+/// Empty args fall back to a name derived from the wallet address
+pub fn parse_username(args: &[u8], authority: &Pubkey) -> Result<String> {
+    if args.is_empty() {
+        return Ok(format!("Player{}", &authority.to_string()[..8]));
+    }
+
+    let username_len = args[0] as usize;
+    if username_len == 0 || username_len > 32 || args.len() < 1 + username_len {
+        return Err(InitPlayerError::InvalidUsername.into());
+    }
+
+    let username_bytes = &args[1..1 + username_len];
+    String::from_utf8(username_bytes.to_vec())
+        .map_err(|_| InitPlayerError::InvalidUsername.into())
+}
+
+/// Normalized seed for the username init_player will claim, empty if the args are malformed
+pub fn username_seed(args: &[u8], authority: &Pubkey) -> Vec<u8> {
+    parse_username(args, authority)
+        .map(|username| normalize_username(&username))
+        .unwrap_or_default()
+}
@@ -1,6 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { MapRegistry } from "../target/types/map_registry";
+import { createHash } from "crypto";
 
 // Simple assertion helpers
 function assertEqual<T>(actual: T, expected: T, message?: string) {
@@ -55,6 +56,37 @@ function assertDefined(value: any, message?: string) {
   }
 }
 
+// Expects the transaction to fail with the given program error
+async function assertFails(promise: Promise<any>, errorCode: string) {
+  try {
+    await promise;
+  } catch (error) {
+    assertInclude(error.toString(), errorCode, `Should throw ${errorCode}, got: ${error.toString()}`);
+    return;
+  }
+  assertFail(`Should have thrown ${errorCode}`);
+}
+
+// Map object with a 1.0 scale at (x, 0, 0)
+function mapObject(modelType: string, x = 0): any {
+  return {
+    modelType: { [modelType]: {} },
+    posX: x, posY: 0, posZ: 0,
+    rotX: 0, rotY: 0, rotZ: 0,
+    scaleX: 10, scaleY: 10, scaleZ: 10,
+    colorR: 255, colorG: 255, colorB: 255,
+  };
+}
+
+// One spawn point per team followed by `cubes` cubes
+function playableObjects(cubes = 0): any[] {
+  const objects = [mapObject("spawnPointBlue", -10), mapObject("spawnPointRed", 10)];
+  for (let i = 0; i < cubes; i++) {
+    objects.push(mapObject("cube", i));
+  }
+  return objects;
+}
+
 describe.only("map-registry", () => {
   // Configure the client to use the local cluster
   const provider = anchor.AnchorProvider.env();
@@ -68,6 +100,10 @@ describe.only("map-registry", () => {
   const MAP_METADATA_SEED = Buffer.from("fps.so map-metadata");
   const MAP_DATA_SEED = Buffer.from("fps.so map-data");
   const USER_MAP_INDEX_SEED = Buffer.from("fps.so user-map-index");
+  const MAP_INDEX_SEED = Buffer.from("fps.so map-index");
+  const MAP_REVISION_SEED = Buffer.from("fps.so map-revision");
+  const MAP_RATING_SEED = Buffer.from("fps.so map-rating");
+  const BPF_LOADER_UPGRADEABLE_ID = new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
 
   // Derive PDAs
   const [mapRegistryPDA] = anchor.web3.PublicKey.findProgramAddressSync(
@@ -80,6 +116,141 @@ describe.only("map-registry", () => {
     program.programId
   );
 
+  // Holds the upgrade authority that may initialize the registry
+  const [programDataPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
+
+  // Map index pages: kind 0 = all maps, 1 = category, 2 = tag
+  function mapIndexPDA(kind: number, label: string, page: number) {
+    const pageBytes = Buffer.alloc(4);
+    pageBytes.writeUInt32LE(page);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [MAP_INDEX_SEED, Buffer.from([kind]), Buffer.from(label), pageBytes],
+      program.programId
+    )[0];
+  }
+
+  const globalIndexPDA = mapIndexPDA(0, "", 0);
+
+  function mapPDAs(mapId: string) {
+    const [mapMetadataPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [MAP_METADATA_SEED, Buffer.from(mapId)],
+      program.programId
+    );
+    const [mapDataPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+      [MAP_DATA_SEED, Buffer.from(mapId)],
+      program.programId
+    );
+    return { mapMetadataPDA, mapDataPDA };
+  }
+
+  // Serialized objects of a MapData account, without the discriminator and length prefix
+  async function rawObjects(mapDataPDA: anchor.web3.PublicKey) {
+    const account = await provider.connection.getAccountInfo(mapDataPDA);
+    const count = account.data.readUInt32LE(8);
+    return account.data.subarray(12, 12 + count * 19);
+  }
+
+  function sha256(bytes: Buffer) {
+    return Array.from(createHash("sha256").update(bytes).digest());
+  }
+
+  // Revisions are keyed on the map's registry nonce so a reused map_id never collides
+  function revisionPDA(mapId: string, nonce: anchor.BN, version: number) {
+    const versionBytes = Buffer.alloc(4);
+    versionBytes.writeUInt32LE(version);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [MAP_REVISION_SEED, Buffer.from(mapId), nonce.toArrayLike(Buffer, "le", 8), versionBytes],
+      program.programId
+    )[0];
+  }
+
+  // Serialized objects of a MapRevision, after its fixed fields
+  async function rawRevisionObjects(mapRevisionPDA: anchor.web3.PublicKey) {
+    const account = await provider.connection.getAccountInfo(mapRevisionPDA);
+    return account.data.subarray(96);
+  }
+
+  // Index pages passed to set_map_status and delete_map
+  function indexPageAccounts(pages: anchor.web3.PublicKey[]) {
+    return pages.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+  }
+
+  async function fundedKeypair() {
+    const keypair = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      2000000000 // 2 SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    return keypair;
+  }
+
+  // Create a draft map, listed on the first global index page
+  async function createUserMap(mapId: string, objects: any[], creator?: anchor.web3.Keypair) {
+    const signer = creator ? creator.publicKey : user;
+    await program.methods
+      .createMap(mapId, `Map ${mapId}`, "", false, objects)
+      .accountsPartial({
+        mapRegistry: mapRegistryPDA,
+        mapIndex: globalIndexPDA,
+        user: signer,
+      })
+      .signers(creator ? [creator] : [])
+      .rpc();
+    return mapPDAs(mapId);
+  }
+
+  // Start a revision of the map's current contents and copy it over `maxObjects` at a time
+  async function publishRevision(mapId: string, maxObjects = 500, creator?: anchor.web3.Keypair) {
+    const { mapMetadataPDA, mapDataPDA } = mapPDAs(mapId);
+    const signer = creator ? creator.publicKey : user;
+    const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+    const mapRevisionPDA = revisionPDA(mapId, metadata.nonce, metadata.version + 1);
+
+    await program.methods
+      .publishMapRevision()
+      .accountsPartial({
+        mapMetadata: mapMetadataPDA,
+        mapDataAccount: mapDataPDA,
+        mapRevision: mapRevisionPDA,
+        user: signer,
+      })
+      .signers(creator ? [creator] : [])
+      .rpc();
+
+    let revision = await program.account.mapRevision.fetch(mapRevisionPDA);
+    while (revision.copiedCount < revision.objectCount) {
+      await program.methods
+        .copyRevisionObjects(maxObjects)
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          mapDataAccount: mapDataPDA,
+          mapRevision: mapRevisionPDA,
+          user: signer,
+        })
+        .signers(creator ? [creator] : [])
+        .rpc();
+      revision = await program.account.mapRevision.fetch(mapRevisionPDA);
+    }
+    return mapRevisionPDA;
+  }
+
+  async function finalizeMap(mapId: string, creator?: anchor.web3.Keypair) {
+    const { mapMetadataPDA, mapDataPDA } = mapPDAs(mapId);
+    await program.methods
+      .finalizeMap()
+      .accountsPartial({
+        mapMetadata: mapMetadataPDA,
+        mapDataAccount: mapDataPDA,
+        user: creator ? creator.publicKey : user,
+      })
+      .signers(creator ? [creator] : [])
+      .rpc();
+  }
+
   console.log("Program ID:", program.programId.toString());
   console.log("Map Registry PDA:", mapRegistryPDA.toString());
   console.log("User:", user.toString());
@@ -88,6 +259,9 @@ describe.only("map-registry", () => {
     it("Initializes the global map registry", async () => {
       const tx = await program.methods
         .initialize()
+        .accountsPartial({
+          programData: programDataPDA,
+        })
         .rpc();
 
       console.log("Initialize transaction signature:", tx);
@@ -96,16 +270,48 @@ describe.only("map-registry", () => {
       const registry = await program.account.mapRegistry.fetch(mapRegistryPDA);
 
       // Verify initial values
+      assertEqual(registry.admin.toString(), user.toString(), "Upgrade authority should be the admin");
       assertEqual(registry.defaultMapsCount, 0, "Default maps count should be 0");
       assertEqual(registry.userMapsCount, 0, "User maps count should be 0");
     });
+
+    it("Fails to initialize twice", async () => {
+      try {
+        await program.methods
+          .initialize()
+          .accountsPartial({
+            programData: programDataPDA,
+          })
+          .rpc();
+
+        assertFail("Should not initialize the registry twice");
+      } catch (error) {
+        assertInclude(error.toString(), "already in use");
+      }
+    });
+
+    it("Creates the first page of the global map index", async () => {
+      const tx = await program.methods
+        .initMapIndexPage({ all: {} }, "", 0)
+        .accountsPartial({
+          mapIndex: globalIndexPDA,
+        })
+        .rpc();
+
+      console.log("Init map index page transaction signature:", tx);
+
+      const page = await program.account.mapIndexPage.fetch(globalIndexPDA);
+      assertDeepEqual(page.kind, { all: {} }, "Page should belong to the global index");
+      assertEqual(page.page, 0, "Page number should be 0");
+      assertEqual(page.mapIds.length, 0, "Page should start empty");
+    });
   });
 
   describe("Create Maps", () => {
     const mapId1 = "test-map-1";
     const mapName1 = "Test Map One";
     const mapDescription1 = "This is the first test map";
-    const mapData1 = Buffer.from("map_data_here_123");
+    const mapData1 = playableObjects(3);
 
     let mapMetadataPDA1: anchor.web3.PublicKey;
     let mapDataPDA1: anchor.web3.PublicKey;
@@ -131,8 +337,9 @@ describe.only("map-registry", () => {
           false, // is_default = false (user map)
           mapData1
         )
-        .accounts({
+        .accountsPartial({
           mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
         })
         .rpc();
 
@@ -145,13 +352,16 @@ describe.only("map-registry", () => {
       assertEqual(metadata.description, mapDescription1, "Map description should match");
       assertEqual(metadata.creator.toString(), user.toString(), "Creator should be the user");
       assertEqual(metadata.isDefault, false, "Should be a user map");
+      assertDeepEqual(metadata.status, { draft: {} }, "New maps should start as drafts");
+      assertEqual(metadata.blueSpawnCount, 1, "Blue spawn should be counted");
+      assertEqual(metadata.redSpawnCount, 1, "Red spawn should be counted");
       assertTrue(metadata.createdAt.gt(new anchor.BN(0)), "Created at timestamp should be set");
       assertEqual(metadata.createdAt.toString(), metadata.updatedAt.toString(), `Created ${metadata.createdAt} and updated timestamps ${metadata.updatedAt} should match initially`);
 
       // Fetch and verify map data
       const mapDataAccount = await program.account.mapData.fetch(mapDataPDA1);
       assertDeepEqual(
-        Buffer.from(mapDataAccount.data),
+        mapDataAccount.objects,
         mapData1,
         "Map data should match"
       );
@@ -165,13 +375,18 @@ describe.only("map-registry", () => {
       const userIndex = await program.account.userMapIndex.fetch(userMapIndexPDA);
       assertEqual(userIndex.mapCount, 1, "User should have 1 map");
       assertEqual(userIndex.mapIds[0], mapId1, "User's map list should contain the map ID");
+
+      // Fetch and verify the global map index
+      const page = await program.account.mapIndexPage.fetch(globalIndexPDA);
+      assertTrue(page.mapIds.includes(mapId1), "Global index should list the map");
+      assertEqual(metadata.indexPage, 0, "Map should remember its index page");
     });
 
     it("Creates a default map successfully", async () => {
       const mapId2 = "default-map-1";
       const mapName2 = "Default Map";
       const mapDescription2 = "This is a default map";
-      const mapData2 = Buffer.from("default_map_data");
+      const mapData2 = playableObjects();
 
       const [mapMetadataPDA2] = anchor.web3.PublicKey.findProgramAddressSync(
         [MAP_METADATA_SEED, Buffer.from(mapId2)],
@@ -186,8 +401,9 @@ describe.only("map-registry", () => {
           true, // is_default = true
           mapData2
         )
-        .accounts({
+        .accountsPartial({
           mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
         })
         .rpc();
 
@@ -207,8 +423,8 @@ describe.only("map-registry", () => {
       const mapId3 = "large-map";
       const mapName3 = "Too Large Map";
       const mapDescription3 = "This map has too much data";
-      // Create data larger than 10KB (10,000 bytes)
-      const largeMapData = Buffer.alloc(10001, "x");
+      // More objects than MapData::MAX_OBJECTS
+      const largeMapData = playableObjects(10_000);
 
       try {
         await program.methods
@@ -217,10 +433,11 @@ describe.only("map-registry", () => {
             mapName3,
             mapDescription3,
             false,
-            (largeMapData)
+            largeMapData
           )
-          .accounts({
+          .accountsPartial({
             mapRegistry: mapRegistryPDA,
+            mapIndex: globalIndexPDA,
           })
           .rpc();
 
@@ -245,7 +462,7 @@ describe.only("map-registry", () => {
     const mapId = "update-test-map";
     const originalName = "Original Name";
     const originalDescription = "Original Description";
-    const mapData = Buffer.from("some_map_data");
+    const mapData = playableObjects();
 
     let mapMetadataPDA: anchor.web3.PublicKey;
 
@@ -258,8 +475,9 @@ describe.only("map-registry", () => {
       // Create the map first
       await program.methods
         .createMap(mapId, originalName, originalDescription, false, mapData)
-        .accounts({
+        .accountsPartial({
           mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
         })
         .rpc();
     });
@@ -360,7 +578,7 @@ describe.only("map-registry", () => {
     const mapId = "data-update-test";
     const mapName = "Data Update Test Map";
     const mapDescription = "Testing data updates";
-    const originalData = Buffer.from("original_data");
+    const originalData = playableObjects(1);
 
     let mapMetadataPDA: anchor.web3.PublicKey;
     let mapDataPDA: anchor.web3.PublicKey;
@@ -379,14 +597,15 @@ describe.only("map-registry", () => {
       // Create the map first
       await program.methods
         .createMap(mapId, mapName, mapDescription, false, originalData)
-        .accounts({
+        .accountsPartial({
           mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
         })
         .rpc();
     });
 
     it("Updates map data successfully", async () => {
-      const newData = Buffer.from("updated_map_data_with_more_content");
+      const newData = playableObjects(5);
 
       const tx = await program.methods
         .updateMapData(newData)
@@ -401,14 +620,14 @@ describe.only("map-registry", () => {
 
       const mapDataAccount = await program.account.mapData.fetch(mapDataPDA);
       assertDeepEqual(
-        Buffer.from(mapDataAccount.data),
+        mapDataAccount.objects,
         newData,
         "Map data should be updated"
       );
     });
 
     it("Updates to smaller data successfully (realloc)", async () => {
-      const smallerData = Buffer.from("small");
+      const smallerData = playableObjects();
 
       const tx = await program.methods
         .updateMapData(smallerData)
@@ -422,18 +641,18 @@ describe.only("map-registry", () => {
 
       const mapDataAccount = await program.account.mapData.fetch(mapDataPDA);
       assertDeepEqual(
-        Buffer.from(mapDataAccount.data),
+        mapDataAccount.objects,
         smallerData,
         "Map data should be updated to smaller size"
       );
     });
 
     it("Fails to update data exceeding maximum size", async () => {
-      const largeData = Buffer.alloc(10001, "x");
+      const largeData = playableObjects(10_000);
 
       try {
         await program.methods
-          .updateMapData((largeData))
+          .updateMapData(largeData)
           .accounts({
             mapMetadata: mapMetadataPDA,
             mapDataAccount: mapDataPDA,
@@ -463,11 +682,11 @@ describe.only("map-registry", () => {
       );
       await provider.connection.confirmTransaction(airdropSig);
 
-      const newData = Buffer.from("hacked_data");
+      const newData = playableObjects(1);
 
       try {
         await program.methods
-          .updateMapData((newData))
+          .updateMapData(newData)
           .accounts({
             mapMetadata: mapMetadataPDA,
             mapDataAccount: mapDataPDA,
@@ -492,7 +711,7 @@ describe.only("map-registry", () => {
     const mapId = "delete-test-map";
     const mapName = "Delete Test Map";
     const mapDescription = "This map will be deleted";
-    const mapData = Buffer.from("temporary_data");
+    const mapData = playableObjects();
 
     let mapMetadataPDA: anchor.web3.PublicKey;
     let mapDataPDA: anchor.web3.PublicKey;
@@ -510,9 +729,10 @@ describe.only("map-registry", () => {
 
       // Create the map
       await program.methods
-        .createMap(mapId, mapName, mapDescription, false, (mapData))
-        .accounts({
+        .createMap(mapId, mapName, mapDescription, false, mapData)
+        .accountsPartial({
           mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
         })
         .rpc();
     });
@@ -523,10 +743,11 @@ describe.only("map-registry", () => {
 
       const tx = await program.methods
         .deleteMap()
-        .accounts({
+        .accountsPartial({
           mapMetadata: mapMetadataPDA,
           mapDataAccount: mapDataPDA,
           userMapIndex: userMapIndexPDA,
+          mapIndex: globalIndexPDA,
           creator: user,
         })
         .rpc();
@@ -561,6 +782,10 @@ describe.only("map-registry", () => {
         "Map ID should be removed from user index"
       );
 
+      // Verify the global map index is updated
+      const page = await program.account.mapIndexPage.fetch(globalIndexPDA);
+      assertNotInclude(page.mapIds, mapId, "Map ID should be removed from the global index");
+
       // Verify rent was refunded (balance should increase)
       const userBalanceAfter = await provider.connection.getBalance(user);
       assertTrue(
@@ -581,11 +806,13 @@ describe.only("map-registry", () => {
       try {
         await program.methods
           .deleteMap()
-          .accounts({
+          .accountsPartial({
             mapMetadata: mapMetadataPDA,
             mapDataAccount: mapDataPDA,
             userMapIndex: userMapIndexPDA,
+            mapIndex: globalIndexPDA,
             creator: user,
+            user: otherUser.publicKey,
           })
           .signers([otherUser])
           .rpc();
@@ -608,7 +835,7 @@ describe.only("map-registry", () => {
         const mapId = `multi-map-${i}`;
         const mapName = `Map ${i}`;
         const mapDescription = `Description for map ${i}`;
-        const mapData = Buffer.from(`data_${i}`);
+        const mapData = playableObjects(i);
 
         const [mapMetadataPDA] = anchor.web3.PublicKey.findProgramAddressSync(
           [MAP_METADATA_SEED, Buffer.from(mapId)],
@@ -621,9 +848,10 @@ describe.only("map-registry", () => {
         );
 
         await program.methods
-          .createMap(mapId, mapName, mapDescription, false, (mapData))
-          .accounts({
+          .createMap(mapId, mapName, mapDescription, false, mapData)
+          .accountsPartial({
             mapRegistry: mapRegistryPDA,
+            mapIndex: globalIndexPDA,
           })
           .rpc();
 
@@ -647,4 +875,1105 @@ describe.only("map-registry", () => {
       console.log(`Successfully created and verified ${numMaps} maps`);
     });
   });
+
+  describe("Registry Admin", () => {
+    const { mapMetadataPDA } = mapPDAs("multi-map-0");
+
+    let outsider: anchor.web3.Keypair;
+
+    function transferAdmin(newAdmin: anchor.web3.PublicKey, signer?: anchor.web3.Keypair) {
+      return program.methods
+        .transferRegistryAdmin(newAdmin)
+        .accountsPartial({
+          mapRegistry: mapRegistryPDA,
+          admin: signer ? signer.publicKey : user,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    }
+
+    function setDefault(isDefault: boolean, signer?: anchor.web3.Keypair) {
+      return program.methods
+        .setMapDefault(isDefault)
+        .accountsPartial({
+          mapRegistry: mapRegistryPDA,
+          mapMetadata: mapMetadataPDA,
+          admin: signer ? signer.publicKey : user,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    }
+
+    before(async () => {
+      outsider = await fundedKeypair();
+    });
+
+    it("Fails to transfer the admin role as a non-admin", async () => {
+      await assertFails(transferAdmin(outsider.publicKey, outsider), "NotRegistryAdmin");
+    });
+
+    it("Fails to promote a map as a non-admin", async () => {
+      await assertFails(setDefault(true, outsider), "NotRegistryAdmin");
+    });
+
+    it("Promotes a map to a default map and back", async () => {
+      const initial = await program.account.mapRegistry.fetch(mapRegistryPDA);
+
+      await setDefault(true);
+
+      let registry = await program.account.mapRegistry.fetch(mapRegistryPDA);
+      assertEqual((await program.account.mapMetadata.fetch(mapMetadataPDA)).isDefault, true, "Map should be a default map");
+      assertEqual(registry.defaultMapsCount, initial.defaultMapsCount + 1, "Default maps count should go up");
+      assertEqual(registry.userMapsCount, initial.userMapsCount - 1, "User maps count should go down");
+
+      await setDefault(false);
+
+      registry = await program.account.mapRegistry.fetch(mapRegistryPDA);
+      assertEqual((await program.account.mapMetadata.fetch(mapMetadataPDA)).isDefault, false, "Map should be a user map again");
+      assertEqual(registry.defaultMapsCount, initial.defaultMapsCount, "Default maps count should be restored");
+      assertEqual(registry.userMapsCount, initial.userMapsCount, "User maps count should be restored");
+    });
+
+    it("Hands the admin role over and back", async () => {
+      await transferAdmin(outsider.publicKey);
+
+      let registry = await program.account.mapRegistry.fetch(mapRegistryPDA);
+      assertEqual(registry.admin.toString(), outsider.publicKey.toString(), "Outsider should be the admin");
+
+      // The previous admin has no say any more
+      await assertFails(setDefault(true), "NotRegistryAdmin");
+
+      await transferAdmin(user, outsider);
+
+      registry = await program.account.mapRegistry.fetch(mapRegistryPDA);
+      assertEqual(registry.admin.toString(), user.toString(), "Admin role should be back with the user");
+    });
+  });
+
+  describe("Browse Map Index", () => {
+    const mapId = "browse-test-map";
+    const category = "ctf";
+    const tags = ["small", "1v1"];
+
+    // Category page first, then one page per tag in tag order
+    const labelPages = [mapIndexPDA(1, category, 0), ...tags.map((tag) => mapIndexPDA(2, tag, 0))];
+
+    let mapMetadataPDA: anchor.web3.PublicKey;
+    let mapDataPDA: anchor.web3.PublicKey;
+
+    before(async () => {
+      ({ mapMetadataPDA, mapDataPDA } = await createUserMap(mapId, playableObjects(2)));
+
+      await program.methods
+        .updateMapLabels(category, tags)
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+        })
+        .rpc();
+      await finalizeMap(mapId);
+
+      await program.methods
+        .initMapIndexPage({ category: {} }, category, 0)
+        .accountsPartial({
+          mapIndex: labelPages[0],
+        })
+        .rpc();
+      for (let i = 0; i < tags.length; i++) {
+        await program.methods
+          .initMapIndexPage({ tag: {} }, tags[i], 0)
+          .accountsPartial({
+            mapIndex: labelPages[i + 1],
+          })
+          .rpc();
+      }
+    });
+
+    it("Fails to publish without a published revision", async () => {
+      await assertFails(
+        program.methods
+          .setMapStatus({ published: {} })
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+          })
+          .remainingAccounts(indexPageAccounts(labelPages))
+          .rpc(),
+        "NoPublishedRevision"
+      );
+    });
+
+    it("Fails to publish without the category and tag pages", async () => {
+      await publishRevision(mapId);
+
+      await assertFails(
+        program.methods
+          .setMapStatus({ published: {} })
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+          })
+          .remainingAccounts(indexPageAccounts(labelPages.slice(0, 1)))
+          .rpc(),
+        "MissingMapIndexPage"
+      );
+    });
+
+    it("Fails to publish with the pages in the wrong order", async () => {
+      await assertFails(
+        program.methods
+          .setMapStatus({ published: {} })
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+          })
+          .remainingAccounts(indexPageAccounts([...labelPages].reverse()))
+          .rpc(),
+        "InvalidMapIndexPage"
+      );
+    });
+
+    it("Lists a published map in its category and tag indexes", async () => {
+      const tx = await program.methods
+        .setMapStatus({ published: {} })
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+        })
+        .remainingAccounts(indexPageAccounts(labelPages))
+        .rpc();
+
+      console.log("Publish map transaction signature:", tx);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertDeepEqual(metadata.status, { published: {} }, "Map should be published");
+      assertEqual(metadata.isListed, true, "Map should be listed");
+      assertEqual(metadata.categoryPage, 0, "Map should remember its category page");
+      assertDeepEqual(metadata.tagPages, [0, 0], "Map should remember its tag pages");
+
+      for (const pagePDA of labelPages) {
+        const page = await program.account.mapIndexPage.fetch(pagePDA);
+        assertTrue(page.mapIds.includes(mapId), "Index page should list the map");
+      }
+    });
+
+    it("Fails to change labels while listed", async () => {
+      await assertFails(
+        program.methods
+          .updateMapLabels("dm", [])
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+          })
+          .rpc(),
+        "MapListed"
+      );
+    });
+
+    it("Unlists the map when it leaves Published", async () => {
+      await program.methods
+        .setMapStatus({ unlisted: {} })
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+        })
+        .remainingAccounts(indexPageAccounts(labelPages))
+        .rpc();
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertDeepEqual(metadata.status, { unlisted: {} }, "Map should be unlisted");
+      assertEqual(metadata.isListed, false, "Map should no longer be listed");
+
+      for (const pagePDA of labelPages) {
+        const page = await program.account.mapIndexPage.fetch(pagePDA);
+        assertNotInclude(page.mapIds, mapId, "Index page should no longer list the map");
+      }
+
+      // Still in the global index
+      const globalPage = await program.account.mapIndexPage.fetch(globalIndexPDA);
+      assertTrue(globalPage.mapIds.includes(mapId), "Global index should still list the map");
+    });
+
+    it("Removes a published map from every index when it is deleted", async () => {
+      await program.methods
+        .setMapStatus({ published: {} })
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+        })
+        .remainingAccounts(indexPageAccounts(labelPages))
+        .rpc();
+
+      await program.methods
+        .deleteMap()
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          mapDataAccount: mapDataPDA,
+          userMapIndex: userMapIndexPDA,
+          mapIndex: globalIndexPDA,
+          creator: user,
+        })
+        .remainingAccounts(indexPageAccounts(labelPages))
+        .rpc();
+
+      for (const pagePDA of [globalIndexPDA, ...labelPages]) {
+        const page = await program.account.mapIndexPage.fetch(pagePDA);
+        assertNotInclude(page.mapIds, mapId, "Index page should no longer list the map");
+      }
+    });
+  });
+
+  describe("Chunked Upload", () => {
+    const mapId = "chunked-upload-map";
+    // Three chunks of 30 objects, each comfortably inside one transaction
+    const chunks = [playableObjects(28), playableObjects(28), playableObjects(28)];
+
+    let mapMetadataPDA: anchor.web3.PublicKey;
+    let mapDataPDA: anchor.web3.PublicKey;
+
+    before(async () => {
+      ({ mapMetadataPDA, mapDataPDA } = await createUserMap(mapId, []));
+    });
+
+    it("Appends objects in chunks", async () => {
+      for (const chunk of chunks) {
+        await program.methods
+          .appendMapObjects(chunk)
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+            mapDataAccount: mapDataPDA,
+          })
+          .rpc();
+      }
+
+      const mapDataAccount = await program.account.mapData.fetch(mapDataPDA);
+      assertDeepEqual(mapDataAccount.objects, chunks.flat(), "Chunks should be stored in order");
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.blueSpawnCount, chunks.length, "Blue spawns of every chunk should be counted");
+      assertDeepEqual(metadata.contentHash, new Array(32).fill(0), "Nothing should be hashed before a revision is published");
+    });
+
+    it("Overwrites objects at an offset", async () => {
+      const replacement = [mapObject("sphere", 42), mapObject("cylinder", 43)];
+
+      await program.methods
+        .writeMapObjects(30, replacement)
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          mapDataAccount: mapDataPDA,
+        })
+        .rpc();
+
+      const mapDataAccount = await program.account.mapData.fetch(mapDataPDA);
+      assertEqual(mapDataAccount.objects.length, 90, "Overwriting should not grow the map");
+      assertDeepEqual(mapDataAccount.objects.slice(30, 32), replacement, "Objects at the offset should be replaced");
+
+      // The second chunk's spawn points were overwritten
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.blueSpawnCount, chunks.length - 1, "Overwritten blue spawn should be uncounted");
+      assertEqual(metadata.redSpawnCount, chunks.length - 1, "Overwritten red spawn should be uncounted");
+    });
+
+    it("Grows the map when writing at the end", async () => {
+      await program.methods
+        .writeMapObjects(90, [mapObject("plane", 50)])
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          mapDataAccount: mapDataPDA,
+        })
+        .rpc();
+
+      const mapDataAccount = await program.account.mapData.fetch(mapDataPDA);
+      assertEqual(mapDataAccount.objects.length, 91, "Map should grow by one object");
+    });
+
+    it("Fails to write past the end of the map", async () => {
+      await assertFails(
+        program.methods
+          .writeMapObjects(92, [mapObject("cube")])
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+            mapDataAccount: mapDataPDA,
+          })
+          .rpc(),
+        "InvalidWriteOffset"
+      );
+    });
+
+    it("Fails to append invalid objects", async () => {
+      const flat = { ...mapObject("cube"), scaleY: 0 };
+
+      await assertFails(
+        program.methods
+          .appendMapObjects([flat])
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+            mapDataAccount: mapDataPDA,
+          })
+          .rpc(),
+        "InvalidObjectScale"
+      );
+    });
+
+    it("Fails to append as someone other than an editor", async () => {
+      const otherUser = await fundedKeypair();
+
+      await assertFails(
+        program.methods
+          .appendMapObjects([mapObject("cube")])
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+            mapDataAccount: mapDataPDA,
+            user: otherUser.publicKey,
+          })
+          .signers([otherUser])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+
+    it("Finalizes the uploaded map", async () => {
+      await finalizeMap(mapId);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertDeepEqual(metadata.status, { unlisted: {} }, "Finalized maps should start unlisted");
+
+      await assertFails(finalizeMap(mapId), "MapFinalized");
+    });
+  });
+
+  describe("Patch Map Objects", () => {
+    const mapId = "patch-test-map";
+    // Blue spawn, red spawn, then cubes at x = 0..3
+    const original = playableObjects(4);
+
+    let mapMetadataPDA: anchor.web3.PublicKey;
+    let mapDataPDA: anchor.web3.PublicKey;
+
+    function patchAccounts(signer = user) {
+      return {
+        mapMetadata: mapMetadataPDA,
+        mapDataAccount: mapDataPDA,
+        user: signer,
+      };
+    }
+
+    async function objects() {
+      return (await program.account.mapData.fetch(mapDataPDA)).objects;
+    }
+
+    before(async () => {
+      ({ mapMetadataPDA, mapDataPDA } = await createUserMap(mapId, original));
+    });
+
+    it("Inserts objects, shifting the following ones back", async () => {
+      const inserted = [mapObject("sphere", 90), mapObject("sphere", 91)];
+
+      await program.methods
+        .insertMapObjects(3, inserted)
+        .accountsPartial(patchAccounts())
+        .rpc();
+
+      const after = await objects();
+      assertEqual(after.length, original.length + 2, "Map should grow by two objects");
+      assertDeepEqual(after.slice(3, 5), inserted, "Objects should be inserted at the index");
+      assertDeepEqual(after.slice(5), original.slice(3), "Following objects should move back");
+    });
+
+    it("Replaces objects in place", async () => {
+      const replacement = mapObject("triangle", 77);
+
+      await program.methods
+        .replaceMapObjects([{ index: 2, object: replacement }])
+        .accountsPartial(patchAccounts())
+        .rpc();
+
+      const after = await objects();
+      assertDeepEqual(after[2], replacement, "Object should be replaced");
+      assertEqual(after.length, original.length + 2, "Replacing should not resize the map");
+    });
+
+    it("Moves an object to a new position", async () => {
+      const before = await objects();
+
+      await program.methods
+        .moveMapObject(2, 5)
+        .accountsPartial(patchAccounts())
+        .rpc();
+
+      const after = await objects();
+      assertDeepEqual(after[5], before[2], "Object should end up at the new index");
+      assertDeepEqual(after.slice(2, 5), before.slice(3, 6), "Objects in between should move forward");
+    });
+
+    it("Removes a range of objects", async () => {
+      const before = await objects();
+
+      await program.methods
+        .removeMapObjects(2, 3)
+        .accountsPartial(patchAccounts())
+        .rpc();
+
+      const after = await objects();
+      assertEqual(after.length, before.length - 3, "Map should shrink by three objects");
+      assertDeepEqual(after.slice(2), before.slice(5), "Following objects should move forward");
+
+    });
+
+    it("Keeps the spawn counts in step with every patch", async () => {
+      const spawnCounts = async () => {
+        const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+        return [metadata.blueSpawnCount, metadata.redSpawnCount];
+      };
+
+      await program.methods
+        .insertMapObjects(2, [mapObject("spawnPointBlue", 20)])
+        .accountsPartial(patchAccounts())
+        .rpc();
+      assertDeepEqual(await spawnCounts(), [2, 1], "Inserted blue spawn should be counted");
+
+      await program.methods
+        .replaceMapObjects([{ index: 2, object: mapObject("spawnPointRed", 20) }])
+        .accountsPartial(patchAccounts())
+        .rpc();
+      assertDeepEqual(await spawnCounts(), [1, 2], "Replaced spawn should switch teams");
+
+      await program.methods
+        .removeMapObjects(2, 1)
+        .accountsPartial(patchAccounts())
+        .rpc();
+      assertDeepEqual(await spawnCounts(), [1, 1], "Removed spawn should be uncounted");
+    });
+
+    it("Fails to patch out of bounds", async () => {
+      const count = (await objects()).length;
+
+      await assertFails(
+        program.methods
+          .removeMapObjects(count - 1, 2)
+          .accountsPartial(patchAccounts())
+          .rpc(),
+        "ObjectIndexOutOfBounds"
+      );
+      await assertFails(
+        program.methods
+          .replaceMapObjects([{ index: count, object: mapObject("cube") }])
+          .accountsPartial(patchAccounts())
+          .rpc(),
+        "ObjectIndexOutOfBounds"
+      );
+    });
+
+    it("Fails to patch as someone other than an editor", async () => {
+      const otherUser = await fundedKeypair();
+
+      await assertFails(
+        program.methods
+          .moveMapObject(0, 1)
+          .accountsPartial(patchAccounts(otherUser.publicKey))
+          .signers([otherUser])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+
+    it("Keeps finalized maps playable under patches and chunked writes", async () => {
+      await finalizeMap(mapId);
+
+      // Edits that keep both spawn points go through
+      await program.methods
+        .appendMapObjects([mapObject("cube", 60)])
+        .accountsPartial(patchAccounts())
+        .rpc();
+
+      // Removing the red spawn (index 1) would leave a team without one
+      await assertFails(
+        program.methods
+          .removeMapObjects(1, 1)
+          .accountsPartial(patchAccounts())
+          .rpc(),
+        "NotEnoughSpawnPoints"
+      );
+      await assertFails(
+        program.methods
+          .writeMapObjects(1, [mapObject("cube")])
+          .accountsPartial(patchAccounts())
+          .rpc(),
+        "NotEnoughSpawnPoints"
+      );
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.redSpawnCount, 1, "Red spawn should still be there");
+    });
+  });
+
+  describe("Map Revisions", () => {
+    const mapId = "revision-test-map";
+    const objects = playableObjects(10);
+
+    let mapMetadataPDA: anchor.web3.PublicKey;
+    let mapDataPDA: anchor.web3.PublicKey;
+    let nonce: anchor.BN;
+
+    function revisionAccounts(mapRevision: anchor.web3.PublicKey) {
+      return {
+        mapMetadata: mapMetadataPDA,
+        mapDataAccount: mapDataPDA,
+        mapRevision,
+      };
+    }
+
+    before(async () => {
+      ({ mapMetadataPDA, mapDataPDA } = await createUserMap(mapId, objects));
+      nonce = (await program.account.mapMetadata.fetch(mapMetadataPDA)).nonce;
+    });
+
+    it("Fails to publish a draft", async () => {
+      await assertFails(
+        program.methods
+          .publishMapRevision()
+          .accountsPartial(revisionAccounts(revisionPDA(mapId, nonce, 1)))
+          .rpc(),
+        "MapNotFinalized"
+      );
+    });
+
+    it("Publishes a revision in chunks", async () => {
+      await finalizeMap(mapId);
+      const mapRevisionPDA = revisionPDA(mapId, nonce, 1);
+
+      await program.methods
+        .publishMapRevision()
+        .accountsPartial(revisionAccounts(mapRevisionPDA))
+        .rpc();
+
+      let metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.version, 1, "Version should be bumped");
+      assertEqual(metadata.publishedVersion, 0, "Revision should not be used until it is copied");
+
+      // Five objects per copy, so three copies for twelve objects
+      for (let i = 0; i < 3; i++) {
+        await program.methods
+          .copyRevisionObjects(5)
+          .accountsPartial(revisionAccounts(mapRevisionPDA))
+          .rpc();
+      }
+
+      const revision = await program.account.mapRevision.fetch(mapRevisionPDA);
+      assertEqual(revision.version, 1, "Revision should record its version");
+      assertEqual(revision.copiedCount, objects.length, "Every object should be copied");
+      assertDeepEqual(
+        Array.from(await rawRevisionObjects(mapRevisionPDA)),
+        Array.from(await rawObjects(mapDataPDA)),
+        "Revision should hold the map's objects"
+      );
+      assertDeepEqual(revision.contentHash, sha256(await rawRevisionObjects(mapRevisionPDA)), "Revision should hash its objects");
+      assertEqual(revision.blueSpawnCount, 1, "Revision should count its blue spawn");
+
+      metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.publishedVersion, 1, "Complete revision should become the published one");
+      assertDeepEqual(metadata.contentHash, revision.contentHash, "Map should carry the published revision's hash");
+
+      await assertFails(
+        program.methods
+          .copyRevisionObjects(5)
+          .accountsPartial(revisionAccounts(mapRevisionPDA))
+          .rpc(),
+        "RevisionComplete"
+      );
+    });
+
+    it("Restarts the copy when the map is edited mid-publish", async () => {
+      const mapRevisionPDA = revisionPDA(mapId, nonce, 2);
+
+      await program.methods
+        .publishMapRevision()
+        .accountsPartial(revisionAccounts(mapRevisionPDA))
+        .rpc();
+      await program.methods
+        .copyRevisionObjects(5)
+        .accountsPartial(revisionAccounts(mapRevisionPDA))
+        .rpc();
+
+      // Edit an object that was already copied
+      await program.methods
+        .replaceMapObjects([{ index: 2, object: mapObject("sphere", 33) }])
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          mapDataAccount: mapDataPDA,
+        })
+        .rpc();
+
+      // The in-place edit shows once the last chunk is copied
+      await program.methods
+        .copyRevisionObjects(5)
+        .accountsPartial(revisionAccounts(mapRevisionPDA))
+        .rpc();
+      await program.methods
+        .copyRevisionObjects(5)
+        .accountsPartial(revisionAccounts(mapRevisionPDA))
+        .rpc();
+
+      let revision = await program.account.mapRevision.fetch(mapRevisionPDA);
+      assertEqual(revision.copiedCount, 0, "Copy should start over from the new contents");
+      assertEqual(
+        (await program.account.mapMetadata.fetch(mapMetadataPDA)).publishedVersion,
+        1,
+        "Stale copy should not be published"
+      );
+
+      while (revision.copiedCount < revision.objectCount) {
+        await program.methods
+          .copyRevisionObjects(5)
+          .accountsPartial(revisionAccounts(mapRevisionPDA))
+          .rpc();
+        revision = await program.account.mapRevision.fetch(mapRevisionPDA);
+      }
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertDeepEqual(revision.contentHash, sha256(await rawObjects(mapDataPDA)), "Revision should match the edited map");
+      assertDeepEqual(metadata.contentHash, revision.contentHash, "Map should carry the new revision's hash");
+      assertDeepEqual(
+        Array.from(await rawRevisionObjects(mapRevisionPDA)),
+        Array.from(await rawObjects(mapDataPDA)),
+        "Revision should hold the edited objects"
+      );
+      assertEqual(metadata.publishedVersion, 2, "Second revision should now be published");
+    });
+
+    it("Leaves earlier revisions untouched", async () => {
+      const first = await program.account.mapRevision.fetch(revisionPDA(mapId, nonce, 1));
+      const firstObjects = await rawRevisionObjects(revisionPDA(mapId, nonce, 1));
+
+      assertEqual(first.version, 1, "First revision should keep its version");
+      assertEqual(firstObjects.readUInt8(2 * 19), 0, "First revision should still hold the original cube at index 2");
+    });
+  });
+
+  describe("Map Ratings", () => {
+    const mapId = "rating-test-map";
+
+    let mapMetadataPDA: anchor.web3.PublicKey;
+    let voter: anchor.web3.Keypair;
+
+    function rate(stars: number, liked: boolean, rater: anchor.web3.Keypair) {
+      const [mapRatingPDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [MAP_RATING_SEED, mapMetadataPDA.toBuffer(), rater.publicKey.toBuffer()],
+        program.programId
+      );
+      return program.methods
+        .rateMap(stars, liked)
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          mapRating: mapRatingPDA,
+          user: rater.publicKey,
+        })
+        .signers([rater])
+        .rpc();
+    }
+
+    before(async () => {
+      ({ mapMetadataPDA } = await createUserMap(mapId, playableObjects(1)));
+      voter = await fundedKeypair();
+    });
+
+    it("Fails to rate a draft", async () => {
+      await assertFails(rate(4, true, voter), "MapNotFinalized");
+    });
+
+    it("Rates and likes a map", async () => {
+      await finalizeMap(mapId);
+      await rate(4, true, voter);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.ratingCount, 1, "One rating should be counted");
+      assertEqual(metadata.ratingTotal.toNumber(), 4, "Stars should be added up");
+      assertEqual(metadata.likeCount, 1, "Like should be counted");
+    });
+
+    it("Replaces the previous vote when rating again", async () => {
+      await rate(2, false, voter);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.ratingCount, 1, "Rating again should not add a vote");
+      assertEqual(metadata.ratingTotal.toNumber(), 2, "Old stars should be replaced");
+      assertEqual(metadata.likeCount, 0, "Like should be taken back");
+    });
+
+    it("Averages votes from several wallets", async () => {
+      const secondVoter = await fundedKeypair();
+      await rate(5, true, secondVoter);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.ratingCount, 2, "Both votes should be counted");
+      assertEqual(metadata.ratingTotal.toNumber(), 7, "Stars of both votes should be added up");
+      assertEqual(metadata.likeCount, 1, "Only the second voter likes the map");
+    });
+
+    it("Fails to rate outside 1 to 5 stars", async () => {
+      await assertFails(rate(0, false, voter), "InvalidRating");
+      await assertFails(rate(6, false, voter), "InvalidRating");
+    });
+
+    it("Fails to rate your own map", async () => {
+      const creator = (provider.wallet as anchor.Wallet).payer;
+      await assertFails(rate(5, true, creator), "CannotRateOwnMap");
+    });
+  });
+
+  describe("Map Forks", () => {
+    const parentId = "fork-parent-map";
+    const forkId = "fork-child-map";
+
+    let parent: { mapMetadataPDA: anchor.web3.PublicKey; mapDataPDA: anchor.web3.PublicKey };
+    let fork: { mapMetadataPDA: anchor.web3.PublicKey; mapDataPDA: anchor.web3.PublicKey };
+    let firstRevisionPDA: anchor.web3.PublicKey;
+    let forker: anchor.web3.Keypair;
+
+    function copyForkObjects(maxObjects: number, parentRevision = firstRevisionPDA) {
+      return program.methods
+        .copyForkObjects(maxObjects)
+        .accountsPartial({
+          parentRevision,
+          mapMetadata: fork.mapMetadataPDA,
+          mapDataAccount: fork.mapDataPDA,
+          user: forker.publicKey,
+        })
+        .signers([forker])
+        .rpc();
+    }
+
+    function forkMap(mapId: string, parentRevision: anchor.web3.PublicKey) {
+      return program.methods
+        .forkMap(mapId, "Forked Map", "A remix")
+        .accountsPartial({
+          parentMetadata: parent.mapMetadataPDA,
+          parentRevision,
+          mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
+          user: forker.publicKey,
+        })
+        .signers([forker])
+        .rpc();
+    }
+
+    before(async () => {
+      parent = await createUserMap(parentId, playableObjects(12));
+      fork = mapPDAs(forkId);
+      forker = await fundedKeypair();
+    });
+
+    it("Fails to fork a map that was never published", async () => {
+      const { nonce } = await program.account.mapMetadata.fetch(parent.mapMetadataPDA);
+      await assertFails(forkMap(forkId, revisionPDA(parentId, nonce, 0)), "AccountNotInitialized");
+    });
+
+    it("Starts a fork of the parent's published revision", async () => {
+      await finalizeMap(parentId);
+      firstRevisionPDA = await publishRevision(parentId);
+
+      await forkMap(forkId, firstRevisionPDA);
+
+      const metadata = await program.account.mapMetadata.fetch(fork.mapMetadataPDA);
+      assertEqual(metadata.creator.toString(), forker.publicKey.toString(), "Forker should own the fork");
+      assertEqual(metadata.parentMapId, parentId, "Fork should name its parent");
+      assertEqual(metadata.parentVersion, 1, "Fork should record the parent version");
+      assertEqual(metadata.forkSource.toString(), firstRevisionPDA.toString(), "Fork should copy from the published revision");
+      assertEqual(metadata.forkObjectCount, 14, "Fork should expect every object of the revision");
+      assertEqual(metadata.forkCopiedCount, 0, "Nothing should be copied yet");
+
+      const parentMetadata = await program.account.mapMetadata.fetch(parent.mapMetadataPDA);
+      assertEqual(parentMetadata.forkCount, 1, "Parent should count the fork");
+    });
+
+    it("Blocks edits and finalizing until the copy completes", async () => {
+      await assertFails(
+        program.methods
+          .appendMapObjects([mapObject("cube")])
+          .accountsPartial({
+            mapMetadata: fork.mapMetadataPDA,
+            mapDataAccount: fork.mapDataPDA,
+            user: forker.publicKey,
+          })
+          .signers([forker])
+          .rpc(),
+        "ForkCopyPending"
+      );
+      await assertFails(finalizeMap(forkId, forker), "ForkCopyPending");
+    });
+
+    it("Keeps copying the attributed revision after the parent republishes", async () => {
+      await copyForkObjects(5);
+
+      // The parent changes and publishes version 2 halfway through the copy
+      await program.methods
+        .replaceMapObjects([{ index: 3, object: mapObject("sphere", 99) }])
+        .accountsPartial({
+          mapMetadata: parent.mapMetadataPDA,
+          mapDataAccount: parent.mapDataPDA,
+        })
+        .rpc();
+      const secondRevisionPDA = await publishRevision(parentId);
+
+      await assertFails(copyForkObjects(5, secondRevisionPDA), "InvalidForkSource");
+
+      await copyForkObjects(5);
+      await copyForkObjects(5);
+
+      const metadata = await program.account.mapMetadata.fetch(fork.mapMetadataPDA);
+      const firstRevision = await program.account.mapRevision.fetch(firstRevisionPDA);
+      assertEqual(metadata.forkCopiedCount, 14, "Every object should be copied");
+      assertEqual(metadata.blueSpawnCount, firstRevision.blueSpawnCount, "Fork should count version 1's spawns");
+      assertEqual(metadata.redSpawnCount, firstRevision.redSpawnCount, "Fork should count version 1's spawns");
+      assertDeepEqual(
+        Array.from(await rawObjects(fork.mapDataPDA)),
+        Array.from(await rawRevisionObjects(firstRevisionPDA)),
+        "Fork objects should match the attributed revision"
+      );
+
+      await assertFails(copyForkObjects(5), "ForkComplete");
+    });
+
+    it("Lets the forker finalize the completed fork", async () => {
+      await finalizeMap(forkId, forker);
+
+      const metadata = await program.account.mapMetadata.fetch(fork.mapMetadataPDA);
+      assertDeepEqual(metadata.status, { unlisted: {} }, "Fork should be finalized");
+    });
+
+    it("Fails to fork once the creator disallows it", async () => {
+      await program.methods
+        .setMapForkable(false)
+        .accountsPartial({
+          mapMetadata: parent.mapMetadataPDA,
+        })
+        .rpc();
+
+      const { nonce, publishedVersion } = await program.account.mapMetadata.fetch(parent.mapMetadataPDA);
+      await assertFails(
+        forkMap("fork-blocked-map", revisionPDA(parentId, nonce, publishedVersion)),
+        "MapNotForkable"
+      );
+    });
+  });
+
+  describe("Map Collaborators", () => {
+    const mapId = "collab-test-map";
+
+    let mapMetadataPDA: anchor.web3.PublicKey;
+    let mapDataPDA: anchor.web3.PublicKey;
+    let editor: anchor.web3.Keypair;
+    let admin: anchor.web3.Keypair;
+
+    function userMapIndex(owner: anchor.web3.PublicKey) {
+      return anchor.web3.PublicKey.findProgramAddressSync(
+        [USER_MAP_INDEX_SEED, owner.toBuffer()],
+        program.programId
+      )[0];
+    }
+
+    function setCollaborator(collaborator: anchor.web3.PublicKey, role: any, signer?: anchor.web3.Keypair) {
+      return program.methods
+        .setMapCollaborator(collaborator, role)
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          user: signer ? signer.publicKey : user,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    }
+
+    function appendAs(signer: anchor.web3.Keypair) {
+      return program.methods
+        .appendMapObjects([mapObject("cube", 5)])
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          mapDataAccount: mapDataPDA,
+          user: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    before(async () => {
+      ({ mapMetadataPDA, mapDataPDA } = await createUserMap(mapId, playableObjects()));
+      editor = await fundedKeypair();
+      admin = await fundedKeypair();
+      // Gives the admin a personal index to transfer from
+      await createUserMap("collab-admin-map", playableObjects(), admin);
+    });
+
+    it("Adds an editor who can change the map", async () => {
+      await setCollaborator(editor.publicKey, { editor: {} });
+      await appendAs(editor);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.collaborators.length, 1, "Editor should be added");
+      assertDeepEqual(metadata.collaborators[0].role, { editor: {} }, "Collaborator should be an editor");
+      assertEqual(metadata.creator.toString(), user.toString(), "Creator should not change");
+    });
+
+    it("Fails to finalize or manage collaborators as an editor", async () => {
+      await assertFails(finalizeMap(mapId, editor), "Unauthorized");
+      await assertFails(setCollaborator(admin.publicKey, { admin: {} }, editor), "Unauthorized");
+    });
+
+    it("Lets an admin collaborator finalize and manage collaborators", async () => {
+      await setCollaborator(admin.publicKey, { admin: {} });
+      await finalizeMap(mapId, admin);
+
+      // Promote the editor, then demote them again
+      await setCollaborator(editor.publicKey, { admin: {} }, admin);
+      await setCollaborator(editor.publicKey, { editor: {} }, admin);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertDeepEqual(metadata.status, { unlisted: {} }, "Admin should be able to finalize");
+      assertEqual(metadata.collaborators.length, 2, "Changing a role should not add an entry");
+    });
+
+    it("Fails to rate the map as a collaborator", async () => {
+      const mapRatingPDA = anchor.web3.PublicKey.findProgramAddressSync(
+        [MAP_RATING_SEED, mapMetadataPDA.toBuffer(), editor.publicKey.toBuffer()],
+        program.programId
+      )[0];
+
+      await assertFails(
+        program.methods
+          .rateMap(5, true)
+          .accountsPartial({
+            mapMetadata: mapMetadataPDA,
+            mapRating: mapRatingPDA,
+            user: editor.publicKey,
+          })
+          .signers([editor])
+          .rpc(),
+        "CannotRateOwnMap"
+      );
+    });
+
+    it("Fails to add the creator as a collaborator", async () => {
+      await assertFails(setCollaborator(user, { editor: {} }), "InvalidCollaborator");
+    });
+
+    it("Lets a collaborator remove themselves", async () => {
+      await program.methods
+        .removeMapCollaborator(editor.publicKey)
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          user: editor.publicKey,
+        })
+        .signers([editor])
+        .rpc();
+
+      await assertFails(appendAs(editor), "Unauthorized");
+    });
+
+    function offerMap(newOwner: anchor.web3.PublicKey, signer?: anchor.web3.Keypair) {
+      return program.methods
+        .transferMapOwnership(newOwner)
+        .accountsPartial({
+          mapMetadata: mapMetadataPDA,
+          user: signer ? signer.publicKey : user,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    }
+
+    async function acceptMap(signer: anchor.web3.Keypair, metadataPDA = mapMetadataPDA) {
+      const metadata = await program.account.mapMetadata.fetch(metadataPDA);
+      return program.methods
+        .acceptMapOwnership()
+        .accountsPartial({
+          mapMetadata: metadataPDA,
+          fromMapIndex: userMapIndex(metadata.creator),
+          toMapIndex: userMapIndex(signer.publicKey),
+          user: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+    }
+
+    it("Fails to offer the map as a collaborator", async () => {
+      await assertFails(offerMap(editor.publicKey, admin), "Unauthorized");
+    });
+
+    it("Fails to accept a map without an offer", async () => {
+      await assertFails(acceptMap(admin), "NoPendingTransfer");
+    });
+
+    it("Leaves the map with its creator until the offer is accepted", async () => {
+      await offerMap(admin.publicKey);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.creator.toString(), user.toString(), "Creator should not change yet");
+      assertEqual(metadata.pendingOwner.toString(), admin.publicKey.toString(), "Offer should be open for the admin");
+
+      const toIndex = await program.account.userMapIndex.fetch(userMapIndex(admin.publicKey));
+      assertNotInclude(toIndex.mapIds, mapId, "Map should not be pushed into the recipient's index");
+    });
+
+    it("Fails to accept an offer made to someone else", async () => {
+      await assertFails(acceptMap(editor), "NoPendingTransfer");
+    });
+
+    it("Transfers ownership once the offer is accepted", async () => {
+      await acceptMap(admin);
+
+      const metadata = await program.account.mapMetadata.fetch(mapMetadataPDA);
+      assertEqual(metadata.creator.toString(), admin.publicKey.toString(), "Admin should own the map");
+      assertEqual(metadata.collaborators.length, 0, "New owner should no longer be a collaborator");
+      assertEqual(metadata.pendingOwner.toString(), anchor.web3.PublicKey.default.toString(), "Offer should be closed");
+
+      const fromIndex = await program.account.userMapIndex.fetch(userMapIndexPDA);
+      assertNotInclude(fromIndex.mapIds, mapId, "Map should leave the old owner's index");
+      const toIndex = await program.account.userMapIndex.fetch(userMapIndex(admin.publicKey));
+      assertTrue(toIndex.mapIds.includes(mapId), "Map should join the new owner's index");
+      assertEqual(toIndex.mapCount, 2, "New owner should now have two maps");
+
+      // The previous creator has no rights left
+      await assertFails(
+        program.methods
+          .updateMapMetadata("Taken back", null)
+          .accounts({
+            mapMetadata: mapMetadataPDA,
+          })
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+
+    it("Fails to accept a map into a full personal index", async () => {
+      const collector = await fundedKeypair();
+
+      // 100 maps fill the personal index, spread over fresh global index pages
+      for (let i = 0; i < 100; i++) {
+        const page = 1000 + Math.floor(i / 32);
+        const pagePDA = mapIndexPDA(0, "", page);
+        if (i % 32 === 0) {
+          await program.methods
+            .initMapIndexPage({ all: {} }, "", page)
+            .accountsPartial({
+              mapIndex: pagePDA,
+            })
+            .rpc();
+        }
+        await program.methods
+          .createMap(`collector-map-${i}`, `Collector ${i}`, "", false, [])
+          .accountsPartial({
+            mapRegistry: mapRegistryPDA,
+            mapIndex: pagePDA,
+            user: collector.publicKey,
+          })
+          .signers([collector])
+          .rpc();
+      }
+
+      // Offering is harmless, the recipient can't be made to take it
+      const offered = await createUserMap("collab-offered-map", playableObjects());
+      await program.methods
+        .transferMapOwnership(collector.publicKey)
+        .accountsPartial({
+          mapMetadata: offered.mapMetadataPDA,
+        })
+        .rpc();
+
+      await assertFails(acceptMap(collector, offered.mapMetadataPDA), "UserMapLimitReached");
+
+      const metadata = await program.account.mapMetadata.fetch(offered.mapMetadataPDA);
+      assertEqual(metadata.creator.toString(), user.toString(), "Map should stay with its creator");
+    });
+  });
 });
@@ -1,16 +1,1724 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Matchmaking } from "../target/types/matchmaking";
+import { MapRegistry } from "../target/types/map_registry";
+import { Game as GameProgram } from "../target/types/game";
+import { GetCommitmentSignature } from "@magicblock-labs/ephemeral-rollups-sdk";
 
-describe("matchmaking", () => {
-  // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+// Simple assertion helpers
+function assertEqual<T>(actual: T, expected: T, message?: string) {
+  if (actual !== expected) {
+    throw new Error(message || `Expected ${expected} but got ${actual}`);
+  }
+}
 
-  const program = anchor.workspace.matchmaking as Program<Matchmaking>;
+function assertDeepEqual(actual: any, expected: any, message?: string) {
+  if (JSON.stringify(actual) !== JSON.stringify(expected)) {
+    throw new Error(message || `Expected ${JSON.stringify(expected)} but got ${JSON.stringify(actual)}`);
+  }
+}
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+function assertTrue(value: boolean, message?: string) {
+  if (!value) {
+    throw new Error(message || `Expected true but got false`);
+  }
+}
+
+function assertInclude(str: string, substring: string, message?: string) {
+  if (!str.includes(substring)) {
+    throw new Error(message || `String should include ${substring}`);
+  }
+}
+
+function assertFail(message: string) {
+  throw new Error(message);
+}
+
+// Expects the transaction to fail with the given program error
+async function assertFails(promise: Promise<any>, errorCode: string) {
+  try {
+    await promise;
+  } catch (error) {
+    assertInclude(error.toString(), errorCode, `Should throw ${errorCode}, got: ${error.toString()}`);
+    return;
+  }
+  assertFail(`Should have thrown ${errorCode}`);
+}
+
+// Map object with a 1.0 scale at (x, 0, 0)
+function mapObject(modelType: string, x = 0): any {
+  return {
+    modelType: { [modelType]: {} },
+    posX: x, posY: 0, posZ: 0,
+    rotX: 0, rotY: 0, rotZ: 0,
+    scaleX: 10, scaleY: 10, scaleZ: 10,
+    colorR: 255, colorG: 255, colorB: 255,
+  };
+}
+
+function u32Bytes(value: number) {
+  const bytes = Buffer.alloc(4);
+  bytes.writeUInt32LE(value);
+  return bytes;
+}
+
+describe.only("matchmaking", () => {
+  // Configure the client to use the local cluster
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Matchmaking as Program<Matchmaking>;
+  const mapRegistry = anchor.workspace.MapRegistry as Program<MapRegistry>;
+  const gameProgram = anchor.workspace.Game as Program<GameProgram>;
+  const admin = provider.wallet.publicKey;
+
+  // Matchmaking PDA seeds
+  const PLAYER_SEED = Buffer.from("player");
+  const GAME_SEED = Buffer.from("game");
+  const LOBBY_DIRECTORY_SEED = Buffer.from("lobby_directory");
+  const CONFIG_SEED = Buffer.from("config");
+  const LEVEL_CURVE_SEED = Buffer.from("level_curve");
+  const MATCH_RECORD_SEED = Buffer.from("match_record");
+  const MATCH_HISTORY_SEED = Buffer.from("match_history");
+  const USERNAME_SEED = Buffer.from("username");
+  const QUEUE_SEED = Buffer.from("match_queue");
+  const SEASON_SEED = Buffer.from("season");
+
+  // Map registry PDA seeds
+  const MAP_REGISTRY_SEED = Buffer.from("fps.so map-registry");
+  const MAP_METADATA_SEED = Buffer.from("fps.so map-metadata");
+  const MAP_DATA_SEED = Buffer.from("fps.so map-data");
+  const MAP_REVISION_SEED = Buffer.from("fps.so map-revision");
+  const MAP_INDEX_SEED = Buffer.from("fps.so map-index");
+  const MAP_PLAY_AUTHORITY_SEED = Buffer.from("fps.so map-play");
+
+  // Game program PDA seeds
+  const GAME_PLAYER_SEED = Buffer.from("game_player");
+  const SETTLEMENT_AUTHORITY_SEED = Buffer.from("settlement");
+
+  const BPF_LOADER_UPGRADEABLE_ID = new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+
+  function pda(seeds: Buffer[], programId = program.programId) {
+    return anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+  }
+
+  const configPDA = pda([CONFIG_SEED]);
+  const levelCurvePDA = pda([LEVEL_CURVE_SEED]);
+  const lobbyDirectoryPDA = pda([LOBBY_DIRECTORY_SEED, u32Bytes(0)]);
+  const mapPlayAuthorityPDA = pda([MAP_PLAY_AUTHORITY_SEED]);
+  const settlementAuthorityPDA = pda([SETTLEMENT_AUTHORITY_SEED], gameProgram.programId);
+
+  const mapRegistryPDA = pda([MAP_REGISTRY_SEED], mapRegistry.programId);
+  const globalIndexPDA = pda([MAP_INDEX_SEED, Buffer.from([0]), Buffer.from(""), u32Bytes(0)], mapRegistry.programId);
+
+  // Published map every lobby in these tests plays on
+  const mapId = "matchmaking-arena";
+  const mapMetadataPDA = pda([MAP_METADATA_SEED, Buffer.from(mapId)], mapRegistry.programId);
+  const mapDataPDA = pda([MAP_DATA_SEED, Buffer.from(mapId)], mapRegistry.programId);
+
+  function playerPDA(authority: anchor.web3.PublicKey) {
+    return pda([PLAYER_SEED, authority.toBuffer()]);
+  }
+
+  function gamePlayerPDA(authority: anchor.web3.PublicKey, game: anchor.web3.PublicKey) {
+    return pda([GAME_PLAYER_SEED, authority.toBuffer(), game.toBuffer()], gameProgram.programId);
+  }
+
+  async function mapRevisionPDA(version?: number) {
+    const metadata = await mapRegistry.account.mapMetadata.fetch(mapMetadataPDA);
+    return pda(
+      [
+        MAP_REVISION_SEED,
+        Buffer.from(mapId),
+        metadata.nonce.toArrayLike(Buffer, "le", 8),
+        u32Bytes(version ?? metadata.publishedVersion),
+      ],
+      mapRegistry.programId
+    );
+  }
+
+  async function fundedKeypair() {
+    const keypair = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      2000000000 // 2 SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    return keypair;
+  }
+
+  // New wallet with a registered Player named `username`
+  async function registerPlayer(username: string) {
+    const wallet = await fundedKeypair();
+    const args = Buffer.concat([Buffer.from([username.length]), Buffer.from(username)]);
+
+    await program.methods
+      .initPlayer(args)
+      .accountsPartial({
+        player: playerPDA(wallet.publicKey),
+        usernameRecord: pda([USERNAME_SEED, Buffer.from(username.toLowerCase())]),
+        authority: wallet.publicKey,
+        signingKey: wallet.publicKey,
+      })
+      .signers([wallet])
+      .rpc();
+    return wallet;
+  }
+
+  // Public lobby on the test map, owned by `owner`
+  async function createLobby(owner: anchor.web3.Keypair) {
+    const player = await program.account.player.fetch(playerPDA(owner.publicKey));
+    const gamePDA = pda([GAME_SEED, owner.publicKey.toBuffer(), u32Bytes(player.gameCounter)]);
+
+    await program.methods
+      .initGame(mapId, false)
+      .accountsPartial({
+        game: gamePDA,
+        player: playerPDA(owner.publicKey),
+        lobbyDirectory: lobbyDirectoryPDA,
+        mapMetadata: mapMetadataPDA,
+        mapRevision: await mapRevisionPDA(),
+        authority: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+    return gamePDA;
+  }
+
+  async function joinLobby(gamePDA: anchor.web3.PublicKey, wallet: anchor.web3.Keypair) {
+    await program.methods
+      .joinGame()
+      .accountsPartial({
+        game: gamePDA,
+        player: playerPDA(wallet.publicKey),
+        lobbyDirectory: lobbyDirectoryPDA,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet])
+      .rpc();
+  }
+
+  async function setReady(gamePDA: anchor.web3.PublicKey, wallet: anchor.web3.Keypair) {
+    await program.methods
+      .setReadyState(true)
+      .accountsPartial({
+        game: gamePDA,
+        player: playerPDA(wallet.publicKey),
+        authority: wallet.publicKey,
+      })
+      .signers([wallet])
+      .rpc();
+  }
+
+  async function startLobby(gamePDA: anchor.web3.PublicKey, owner: anchor.web3.Keypair) {
+    await program.methods
+      .startGame()
+      .accountsPartial({
+        game: gamePDA,
+        player: playerPDA(owner.publicKey),
+        lobbyDirectory: lobbyDirectoryPDA,
+        mapMetadata: mapMetadataPDA,
+        mapPlayAuthority: mapPlayAuthorityPDA,
+        mapRegistryProgram: mapRegistry.programId,
+        authority: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  function settlementRecordPDA(gamePDA: anchor.web3.PublicKey) {
+    return pda([MATCH_RECORD_SEED, gamePDA.toBuffer()]);
+  }
+
+  // Report a base layer GamePlayer's stats to matchmaking
+  function settleGamePlayer(gamePDA: anchor.web3.PublicKey, wallet: anchor.web3.Keypair) {
+    return gameProgram.methods
+      .settleGamePlayer()
+      .accountsPartial({
+        gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+        authority: wallet.publicKey,
+        game: gamePDA,
+        player: playerPDA(wallet.publicKey),
+        levelCurve: levelCurvePDA,
+        matchRecord: settlementRecordPDA(gamePDA),
+        matchHistory: pda([MATCH_HISTORY_SEED, wallet.publicKey.toBuffer()]),
+        settlementAuthority: settlementAuthorityPDA,
+        matchmakingProgram: program.programId,
+      })
+      .rpc();
+  }
+
+  // `wallets` must cover the roster and every player holding a reserved slot
+  // `season` is required while a season is running
+  function settleMatch(
+    gamePDA: anchor.web3.PublicKey,
+    wallets: anchor.web3.Keypair[],
+    season: anchor.web3.PublicKey | null = null
+  ) {
+    return program.methods
+      .settleMatch()
+      .accountsPartial({
+        game: gamePDA,
+        levelCurve: levelCurvePDA,
+        matchRecord: settlementRecordPDA(gamePDA),
+        config: configPDA,
+        season,
+      })
+      .remainingAccounts(
+        wallets.map((wallet) => ({
+          pubkey: playerPDA(wallet.publicKey),
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .rpc();
+  }
+
+  // Both players ready up in a lobby owned by `owner` and the match starts
+  async function startMatch(owner: anchor.web3.Keypair, guest: anchor.web3.Keypair) {
+    const gamePDA = await createLobby(owner);
+    await joinLobby(gamePDA, guest);
+    await setReady(gamePDA, owner);
+    await setReady(gamePDA, guest);
+    await startLobby(gamePDA, owner);
+    return gamePDA;
+  }
+
+  // Global config, level curve, lobby directory and a published map
+  // Anything the map registry tests already set up is reused
+  before(async () => {
+    if (!(await program.account.matchmakingConfig.fetchNullable(configPDA))) {
+      await program.methods
+        .initializeConfig()
+        .accountsPartial({
+          programData: pda([program.programId.toBuffer()], BPF_LOADER_UPGRADEABLE_ID),
+        })
+        .rpc();
+      await program.methods.initLevelCurve().rpc();
+    }
+    if (!(await program.account.lobbyDirectory.fetchNullable(lobbyDirectoryPDA))) {
+      await program.methods
+        .initLobbyDirectory(0)
+        .accountsPartial({
+          lobbyDirectory: lobbyDirectoryPDA,
+        })
+        .rpc();
+    }
+
+    if (!(await mapRegistry.account.mapRegistry.fetchNullable(mapRegistryPDA))) {
+      await mapRegistry.methods
+        .initialize()
+        .accountsPartial({
+          programData: pda([mapRegistry.programId.toBuffer()], BPF_LOADER_UPGRADEABLE_ID),
+        })
+        .rpc();
+    }
+    if (!(await mapRegistry.account.mapIndexPage.fetchNullable(globalIndexPDA))) {
+      await mapRegistry.methods
+        .initMapIndexPage({ all: {} }, "", 0)
+        .accountsPartial({
+          mapIndex: globalIndexPDA,
+        })
+        .rpc();
+    }
+
+    const objects = [mapObject("spawnPointBlue", -10), mapObject("spawnPointRed", 10), mapObject("plane")];
+    await mapRegistry.methods
+      .createMap(mapId, "Arena", "Matchmaking test map", false, objects)
+      .accountsPartial({
+        mapRegistry: mapRegistryPDA,
+        mapIndex: globalIndexPDA,
+      })
+      .rpc();
+    await mapRegistry.methods
+      .finalizeMap()
+      .accountsPartial({
+        mapMetadata: mapMetadataPDA,
+        mapDataAccount: mapDataPDA,
+      })
+      .rpc();
+
+    const revisionPDA = await mapRevisionPDA(1);
+    await mapRegistry.methods
+      .publishMapRevision()
+      .accountsPartial({
+        mapMetadata: mapMetadataPDA,
+        mapDataAccount: mapDataPDA,
+        mapRevision: revisionPDA,
+      })
+      .rpc();
+    await mapRegistry.methods
+      .copyRevisionObjects(objects.length)
+      .accountsPartial({
+        mapMetadata: mapMetadataPDA,
+        mapDataAccount: mapDataPDA,
+        mapRevision: revisionPDA,
+      })
+      .rpc();
+    await mapRegistry.methods
+      .setMapStatus({ published: {} })
+      .accountsPartial({
+        mapMetadata: mapMetadataPDA,
+      })
+      .rpc();
+  });
+
+  describe("Map Selection", () => {
+    // Finalized maps start out Unlisted, this one gets a revision but is never published
+    const unlistedMapId = "matchmaking-backroom";
+    const unlistedMetadataPDA = pda([MAP_METADATA_SEED, Buffer.from(unlistedMapId)], mapRegistry.programId);
+    const unlistedDataPDA = pda([MAP_DATA_SEED, Buffer.from(unlistedMapId)], mapRegistry.programId);
+
+    // Never finalized, so it has no revision a lobby could be pinned to
+    const draftMapId = "matchmaking-draft";
+
+    let quinn: anchor.web3.Keypair;
+    let rhea: anchor.web3.Keypair;
+    let unlistedRevisionPDA: anchor.web3.PublicKey;
+    let privateGamePDA: anchor.web3.PublicKey;
+
+    // Metadata and latest published revision of any map
+    async function mapAccounts(id: string) {
+      const mapMetadata = pda([MAP_METADATA_SEED, Buffer.from(id)], mapRegistry.programId);
+      const metadata = await mapRegistry.account.mapMetadata.fetch(mapMetadata);
+      const mapRevision = pda(
+        [MAP_REVISION_SEED, Buffer.from(id), metadata.nonce.toArrayLike(Buffer, "le", 8), u32Bytes(metadata.publishedVersion)],
+        mapRegistry.programId
+      );
+      return { mapMetadata, mapRevision };
+    }
+
+    async function lobbyOn(owner: anchor.web3.Keypair, isPrivate: boolean, id = unlistedMapId) {
+      const player = await program.account.player.fetch(playerPDA(owner.publicKey));
+      const gamePDA = pda([GAME_SEED, owner.publicKey.toBuffer(), u32Bytes(player.gameCounter)]);
+
+      await program.methods
+        .initGame(id, isPrivate)
+        .accountsPartial({
+          game: gamePDA,
+          player: playerPDA(owner.publicKey),
+          lobbyDirectory: lobbyDirectoryPDA,
+          ...(await mapAccounts(id)),
+          authority: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+      return gamePDA;
+    }
+
+    async function updateSettings(gamePDA: anchor.web3.PublicKey, owner: anchor.web3.Keypair, isPrivate: boolean, id: string) {
+      return program.methods
+        .updateLobbySettings(id, "Backroom", isPrivate)
+        .accountsPartial({
+          game: gamePDA,
+          ...(await mapAccounts(id)),
+          lobbyDirectory: lobbyDirectoryPDA,
+          authority: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+    }
+
+    before(async () => {
+      quinn = await registerPlayer("map_quinn");
+      rhea = await registerPlayer("map_rhea");
+
+      const objects = [mapObject("spawnPointBlue", -10), mapObject("spawnPointRed", 10), mapObject("plane")];
+      await mapRegistry.methods
+        .createMap(unlistedMapId, "Backroom", "Unlisted test map", false, objects)
+        .accountsPartial({
+          mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
+        })
+        .rpc();
+      await mapRegistry.methods
+        .finalizeMap()
+        .accountsPartial({
+          mapMetadata: unlistedMetadataPDA,
+          mapDataAccount: unlistedDataPDA,
+        })
+        .rpc();
+
+      const metadata = await mapRegistry.account.mapMetadata.fetch(unlistedMetadataPDA);
+      unlistedRevisionPDA = pda(
+        [MAP_REVISION_SEED, Buffer.from(unlistedMapId), metadata.nonce.toArrayLike(Buffer, "le", 8), u32Bytes(1)],
+        mapRegistry.programId
+      );
+      await mapRegistry.methods
+        .publishMapRevision()
+        .accountsPartial({
+          mapMetadata: unlistedMetadataPDA,
+          mapDataAccount: unlistedDataPDA,
+          mapRevision: unlistedRevisionPDA,
+        })
+        .rpc();
+      await mapRegistry.methods
+        .copyRevisionObjects(objects.length)
+        .accountsPartial({
+          mapMetadata: unlistedMetadataPDA,
+          mapDataAccount: unlistedDataPDA,
+          mapRevision: unlistedRevisionPDA,
+        })
+        .rpc();
+
+      await mapRegistry.methods
+        .createMap(draftMapId, "Draft", "Unfinished test map", false, objects)
+        .accountsPartial({
+          mapRegistry: mapRegistryPDA,
+          mapIndex: globalIndexPDA,
+        })
+        .rpc();
+    });
+
+    it("Fails to open a public lobby on an unlisted map", async () => {
+      await assertFails(lobbyOn(quinn, false), "MapNotPublic");
+    });
+
+    it("Opens a private lobby on an unlisted map", async () => {
+      privateGamePDA = await lobbyOn(quinn, true);
+
+      const game = await program.account.game.fetch(privateGamePDA);
+      assertEqual(game.mapId, unlistedMapId, "Lobby should be on the unlisted map");
+      assertEqual(game.mapVersion, 1, "Lobby should be pinned to the first revision");
+      assertEqual(game.isListed, false, "Private lobby should not be listed");
+    });
+
+    it("Fails to make a lobby on an unlisted map public", async () => {
+      await assertFails(updateSettings(privateGamePDA, quinn, false, unlistedMapId), "MapNotPublic");
+    });
+
+    it("Fails to open a lobby on a draft map", async () => {
+      // A draft has no published revision to pin, so the revision account does not exist
+      await assertFails(lobbyOn(rhea, true, draftMapId), "AccountNotInitialized");
+    });
+
+    it("Fails to use a delisted map", async () => {
+      await mapRegistry.methods
+        .setMapStatus({ delisted: {} })
+        .accountsPartial({
+          mapMetadata: unlistedMetadataPDA,
+        })
+        .rpc();
+
+      await assertFails(lobbyOn(rhea, true), "MapNotPlayable");
+      await assertFails(updateSettings(privateGamePDA, quinn, true, unlistedMapId), "MapNotPlayable");
+    });
+  });
+
+  describe("Usernames", () => {
+    let kate: anchor.web3.Keypair;
+    let liam: anchor.web3.Keypair;
+
+    const usernamePDA = (username: string) => pda([USERNAME_SEED, Buffer.from(username.toLowerCase())]);
+
+    // Case-only renames keep the current record, so no new one is passed
+    function rename(wallet: anchor.web3.Keypair, from: string, to: string) {
+      const sameClaim = from.toLowerCase() === to.toLowerCase();
+      return program.methods
+        .renamePlayer(to)
+        .accountsPartial({
+          player: playerPDA(wallet.publicKey),
+          oldUsernameRecord: usernamePDA(from),
+          newUsernameRecord: sameClaim ? null : usernamePDA(to),
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    before(async () => {
+      kate = await registerPlayer("rename_kate");
+      liam = await registerPlayer("rename_liam");
+    });
+
+    it("Fails to register a name another player holds", async () => {
+      await assertFails(registerPlayer("Rename_Kate"), "already in use");
+    });
+
+    it("Fails to rename to a name another player holds", async () => {
+      await assertFails(rename(liam, "rename_liam", "RENAME_KATE"), "already in use");
+    });
+
+    it("Renames and releases the old name", async () => {
+      await rename(liam, "rename_liam", "rename_mia");
+
+      const player = await program.account.player.fetch(playerPDA(liam.publicKey));
+      assertEqual(player.username, "rename_mia", "Username should change");
+
+      const record = await program.account.usernameRecord.fetch(usernamePDA("rename_mia"));
+      assertEqual(record.owner.toString(), liam.publicKey.toString(), "New name should belong to Liam");
+      const oldRecord = await program.account.usernameRecord.fetchNullable(usernamePDA("rename_liam"));
+      assertEqual(oldRecord, null, "Old name should be released");
+    });
+
+    it("Lets another player claim a released name", async () => {
+      const newcomer = await registerPlayer("rename_liam");
+
+      const record = await program.account.usernameRecord.fetch(usernamePDA("rename_liam"));
+      assertEqual(record.owner.toString(), newcomer.publicKey.toString(), "Released name should be claimable");
+    });
+
+    it("Fails to rename again within the cooldown", async () => {
+      await assertFails(rename(liam, "rename_mia", "rename_noah"), "RenameCooldownActive");
+    });
+
+    it("Changes only the capitalization of a name", async () => {
+      await rename(kate, "rename_kate", "Rename_Kate");
+
+      const player = await program.account.player.fetch(playerPDA(kate.publicKey));
+      assertEqual(player.username, "Rename_Kate", "Display name should change");
+
+      const record = await program.account.usernameRecord.fetch(usernamePDA("rename_kate"));
+      assertEqual(record.owner.toString(), kate.publicKey.toString(), "Kate should keep her claim");
+      assertEqual(record.username, "Rename_Kate", "Record should show the new capitalization");
+    });
+
+    it("Fails to rename to the current name", async () => {
+      await assertFails(rename(kate, "Rename_Kate", "Rename_Kate"), "SameUsername");
+    });
+  });
+
+  describe("Lobby Ownership", () => {
+    let henry: anchor.web3.Keypair;
+    let ivy: anchor.web3.Keypair;
+    let jack: anchor.web3.Keypair;
+    let gamePDA: anchor.web3.PublicKey;
+
+    function leave(wallet: anchor.web3.Keypair, nextOwner: anchor.web3.PublicKey | null) {
+      return program.methods
+        .leaveGame()
+        .accountsPartial({
+          game: gamePDA,
+          player: playerPDA(wallet.publicKey),
+          lobbyDirectory: lobbyDirectoryPDA,
+          nextOwner,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    // Henry on team A, Ivy on team B, Jack on team A behind Henry
+    before(async () => {
+      henry = await registerPlayer("owner_henry");
+      ivy = await registerPlayer("owner_ivy");
+      jack = await registerPlayer("owner_jack");
+
+      gamePDA = await createLobby(henry);
+      await joinLobby(gamePDA, ivy);
+      await joinLobby(gamePDA, jack);
+    });
+
+    it("Fails to hand off ownership without the next owner", async () => {
+      await assertFails(leave(henry, null), "NextOwnerRequired");
+    });
+
+    it("Fails to hand off ownership to anyone but the first remaining player", async () => {
+      await assertFails(leave(henry, playerPDA(ivy.publicKey)), "WrongNextOwner");
+    });
+
+    it("Hands ownership to the next player's wallet", async () => {
+      await leave(henry, playerPDA(jack.publicKey));
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.createdBy.toString(), jack.publicKey.toString(), "Jack's wallet should own the lobby");
+    });
+
+    it("Lets the new owner start the game", async () => {
+      // Nobody is ready, so only the owner check can let Jack start
+      await startLobby(gamePDA, jack);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.gameState, 1, "Game should be running");
+    });
+  });
+
+  describe("Match Queue", () => {
+    // Team deathmatch, region 0 - the queue mode matches the lobbies init_game creates
+    const MODE = 1;
+    const REGION = 0;
+    const queuePDA = pda([QUEUE_SEED, Buffer.from([MODE]), Buffer.from([REGION])]);
+
+    let rita: anchor.web3.Keypair;
+    let sam: anchor.web3.Keypair;
+    let tess: anchor.web3.Keypair;
+    let uma: anchor.web3.Keypair;
+    let vic: anchor.web3.Keypair;
+
+    function enqueue(wallet: anchor.web3.Keypair) {
+      return program.methods
+        .enqueue(MODE, REGION)
+        .accountsPartial({
+          queue: queuePDA,
+          player: playerPDA(wallet.publicKey),
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    function dequeue(wallet: anchor.web3.Keypair) {
+      return program.methods
+        .dequeue()
+        .accountsPartial({
+          queue: queuePDA,
+          player: playerPDA(wallet.publicKey),
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    function queuedPlayers(wallets: anchor.web3.Keypair[]) {
+      return wallets.map((wallet) => ({
+        pubkey: playerPDA(wallet.publicKey),
+        isSigner: false,
+        isWritable: true,
+      }));
+    }
+
+    async function matchQueued(wallets: anchor.web3.Keypair[]) {
+      const queue = await program.account.matchQueue.fetch(queuePDA);
+      const gamePDA = pda([GAME_SEED, queuePDA.toBuffer(), u32Bytes(queue.gamesCreated)]);
+
+      await program.methods
+        .matchQueue(mapId)
+        .accountsPartial({
+          queue: queuePDA,
+          game: gamePDA,
+          mapMetadata: mapMetadataPDA,
+          mapRevision: await mapRevisionPDA(),
+        })
+        .remainingAccounts(queuedPlayers(wallets))
+        .rpc();
+      return gamePDA;
+    }
+
+    function fillFromQueue(gamePDA: anchor.web3.PublicKey, wallets: anchor.web3.Keypair[]) {
+      return program.methods
+        .fillFromQueue()
+        .accountsPartial({
+          queue: queuePDA,
+          game: gamePDA,
+          lobbyDirectory: lobbyDirectoryPDA,
+        })
+        .remainingAccounts(queuedPlayers(wallets))
+        .rpc();
+    }
+
+    function isQueued(queue: any, wallet: anchor.web3.Keypair) {
+      return queue.entries.some((entry) => entry.player.equals(playerPDA(wallet.publicKey)));
+    }
+
+    before(async () => {
+      if (!(await program.account.matchQueue.fetchNullable(queuePDA))) {
+        await program.methods
+          .initMatchQueue(MODE, REGION)
+          .accountsPartial({
+            queue: queuePDA,
+          })
+          .rpc();
+      }
+
+      rita = await registerPlayer("queue_rita");
+      sam = await registerPlayer("queue_sam");
+      tess = await registerPlayer("queue_tess");
+      uma = await registerPlayer("queue_uma");
+      vic = await registerPlayer("queue_vic");
+    });
+
+    it("Fails to leave a queue the player is not in", async () => {
+      await assertFails(dequeue(rita), "PlayerNotQueued");
+    });
+
+    it("Queues a player", async () => {
+      await enqueue(rita);
+
+      const queue = await program.account.matchQueue.fetch(queuePDA);
+      assertTrue(isQueued(queue, rita), "Queue should list Rita");
+
+      const player = await program.account.player.fetch(playerPDA(rita.publicKey));
+      assertEqual(player.currentQueue.toString(), queuePDA.toString(), "Rita should be in the queue");
+      assertTrue(player.queuedAt.toNumber() > 0, "Queue time should be recorded");
+    });
+
+    it("Fails to queue a player twice", async () => {
+      await assertFails(enqueue(rita), "PlayerAlreadyQueued");
+    });
+
+    it("Leaves the queue", async () => {
+      await dequeue(rita);
+
+      const queue = await program.account.matchQueue.fetch(queuePDA);
+      assertTrue(!isQueued(queue, rita), "Queue should no longer list Rita");
+
+      const player = await program.account.player.fetch(playerPDA(rita.publicKey));
+      assertEqual(player.currentQueue, null, "Rita should be out of the queue");
+      assertEqual(player.queuedAt.toNumber(), 0, "Queue time should be cleared");
+    });
+
+    it("Fails to match fewer players than a match needs", async () => {
+      await enqueue(rita);
+
+      await assertFails(matchQueued([rita]), "NotEnoughPlayers");
+    });
+
+    it("Fails to match a player who is not queued", async () => {
+      await assertFails(matchQueued([rita, sam]), "PlayerNotQueued");
+    });
+
+    it("Matches queued players into a new game", async () => {
+      await enqueue(sam);
+      const gamesCreated = (await program.account.matchQueue.fetch(queuePDA)).gamesCreated;
+
+      const gamePDA = await matchQueued([rita, sam]);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.gameState, 0, "Game should be waiting for players to ready up");
+      assertEqual(game.createdBy.toString(), rita.publicKey.toString(), "First matched player should own the game");
+      assertEqual(game.mapId, mapId, "Game should be on the requested map");
+      assertEqual(game.matchType, MODE, "Game should have the queue's mode");
+      assertEqual(game.currentPlayersTeamA, 1, "Teams should be balanced");
+      assertEqual(game.currentPlayersTeamB, 1, "Teams should be balanced");
+
+      const queue = await program.account.matchQueue.fetch(queuePDA);
+      assertEqual(queue.gamesCreated, gamesCreated + 1, "Queue should count the game");
+      assertTrue(!isQueued(queue, rita) && !isQueued(queue, sam), "Matched players should leave the queue");
+
+      for (const wallet of [rita, sam]) {
+        const player = await program.account.player.fetch(playerPDA(wallet.publicKey));
+        assertEqual(player.currentGame.toString(), gamePDA.toString(), "Player should be in the game");
+        assertEqual(player.currentQueue, null, "Player should be out of the queue");
+      }
+    });
+
+    it("Fails to queue a player who is in a game", async () => {
+      await assertFails(enqueue(rita), "PlayerAlreadyInGame");
+    });
+
+    it("Fails to backfill with a player who is not queued", async () => {
+      const gamePDA = await createLobby(tess);
+
+      await assertFails(fillFromQueue(gamePDA, [vic]), "PlayerNotQueued");
+    });
+
+    it("Backfills a waiting lobby from the queue", async () => {
+      const player = await program.account.player.fetch(playerPDA(tess.publicKey));
+      const gamePDA = pda([GAME_SEED, tess.publicKey.toBuffer(), u32Bytes(player.gameCounter - 1)]);
+      await enqueue(uma);
+
+      await fillFromQueue(gamePDA, [uma]);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertTrue(
+        game.teamBPlayers.some((key) => key.equals(playerPDA(uma.publicKey))),
+        "Uma should be seated on the smaller team"
+      );
+
+      const queue = await program.account.matchQueue.fetch(queuePDA);
+      assertTrue(!isQueued(queue, uma), "Uma should leave the queue");
+
+      const directory = await program.account.lobbyDirectory.fetch(lobbyDirectoryPDA);
+      const entry = directory.entries.find((entry) => entry.game.equals(gamePDA));
+      assertEqual(entry.currentPlayersTeamB, 1, "Directory should show the new player count");
+    });
+  });
+
+  describe("Stale Lobbies", () => {
+    const LOBBY_IDLE_TIMEOUT = 60;
+
+    let olivia: anchor.web3.Keypair;
+    let paul: anchor.web3.Keypair;
+    let gamePDA: anchor.web3.PublicKey;
+
+    function setLobbyIdleTimeout(timeout: number) {
+      return program.methods
+        .updateConfig(new anchor.BN(timeout))
+        .accountsPartial({
+          config: configPDA,
+          admin,
+        })
+        .rpc();
+    }
+
+    function closeStaleLobby(creator: anchor.web3.PublicKey) {
+      return program.methods
+        .closeStaleLobby()
+        .accountsPartial({
+          game: gamePDA,
+          creator,
+          config: configPDA,
+          lobbyDirectory: lobbyDirectoryPDA,
+        })
+        .rpc();
+    }
+
+    function forceLeave(wallet: anchor.web3.Keypair) {
+      return program.methods
+        .forceLeaveStaleGame()
+        .accountsPartial({
+          game: gamePDA,
+          player: playerPDA(wallet.publicKey),
+          config: configPDA,
+          lobbyDirectory: lobbyDirectoryPDA,
+          nextOwner: null,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    before(async () => {
+      olivia = await registerPlayer("stale_olivia");
+      paul = await registerPlayer("stale_paul");
+
+      gamePDA = await createLobby(olivia);
+      await joinLobby(gamePDA, paul);
+      await setLobbyIdleTimeout(LOBBY_IDLE_TIMEOUT);
+    });
+
+    after(async () => {
+      await setLobbyIdleTimeout(1800);
+    });
+
+    it("Fails to clean up a lobby that is still active", async () => {
+      await assertFails(closeStaleLobby(olivia.publicKey), "LobbyNotStale");
+      await assertFails(forceLeave(paul), "LobbyNotStale");
+    });
+
+    it("Releases a player from a stale lobby", async () => {
+      await new Promise((resolve) => setTimeout(resolve, (LOBBY_IDLE_TIMEOUT + 2) * 1000));
+      await forceLeave(paul);
+
+      const player = await program.account.player.fetch(playerPDA(paul.publicKey));
+      assertEqual(player.currentGame, null, "Paul should be released");
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.currentPlayersTeamA + game.currentPlayersTeamB, 1, "Only Olivia should be left");
+    });
+
+    it("Fails to send the rent anywhere but the lobby owner", async () => {
+      await assertFails(closeStaleLobby(paul.publicKey), "WrongCreator");
+    });
+
+    it("Closes a stale lobby and refunds its owner", async () => {
+      const rent = await provider.connection.getBalance(gamePDA);
+      const balanceBefore = await provider.connection.getBalance(olivia.publicKey);
+
+      // Anyone may crank it, the provider wallet pays the fee
+      await closeStaleLobby(olivia.publicKey);
+
+      const balanceAfter = await provider.connection.getBalance(olivia.publicKey);
+      assertEqual(balanceAfter - balanceBefore, rent, "Olivia should get the lobby's rent back");
+      assertEqual(await program.account.game.fetchNullable(gamePDA), null, "Game should be closed");
+
+      const directory = await program.account.lobbyDirectory.fetch(lobbyDirectoryPDA);
+      assertTrue(!directory.entries.some((entry) => entry.game.equals(gamePDA)), "Lobby should be unlisted");
+    });
+
+    it("Releases a player whose lobby was closed", async () => {
+      await forceLeave(olivia);
+
+      const player = await program.account.player.fetch(playerPDA(olivia.publicKey));
+      assertEqual(player.currentGame, null, "Olivia should be released");
+    });
+  });
+
+  describe("Pause and Resume", () => {
+    let nina: anchor.web3.Keypair;
+    let otto: anchor.web3.Keypair;
+    let gamePDA: anchor.web3.PublicKey;
+
+    function pause(wallet: anchor.web3.Keypair) {
+      return program.methods
+        .pauseMatch()
+        .accountsPartial({
+          game: gamePDA,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    function resume(wallet: anchor.web3.Keypair) {
+      return program.methods
+        .resumeMatch()
+        .accountsPartial({
+          game: gamePDA,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    // One step forward on the base layer GamePlayer
+    function stepForward(wallet: anchor.web3.Keypair) {
+      return gameProgram.methods
+        .processInput(true, false, false, false, 0, 0, 0, 0.1, gamePDA)
+        .accountsPartial({
+          gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+          game: gamePDA,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    before(async () => {
+      nina = await registerPlayer("pause_nina");
+      otto = await registerPlayer("pause_otto");
+      gamePDA = await startMatch(nina, otto);
+
+      for (const [wallet, team, x] of [[nina, 1, 0], [otto, 2, 5]] as [anchor.web3.Keypair, number, number][]) {
+        await gameProgram.methods
+          .initGamePlayer(gamePDA, team, false, x, 0, 0)
+          .accountsPartial({
+            gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+            authority: wallet.publicKey,
+          })
+          .signers([wallet])
+          .rpc();
+      }
+    });
+
+    it("Fails to pause as a guest", async () => {
+      await assertFails(pause(otto), "NotLobbyOwner");
+    });
+
+    it("Pauses the match", async () => {
+      await pause(nina);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.gameState, 3, "Game should be paused");
+      assertTrue(game.pausedAt.toNumber() > 0, "Pause time should be recorded");
+    });
+
+    it("Rejects input while paused", async () => {
+      await assertFails(stepForward(otto), "MatchPaused");
+      await assertFails(
+        gameProgram.methods
+          .shoot(100)
+          .accountsPartial({
+            shooter: gamePlayerPDA(nina.publicKey, gamePDA),
+            game: gamePDA,
+            authority: nina.publicKey,
+          })
+          .remainingAccounts([
+            { pubkey: gamePlayerPDA(otto.publicKey, gamePDA), isSigner: false, isWritable: true },
+          ])
+          .signers([nina])
+          .rpc(),
+        "MatchPaused"
+      );
+
+      const target = await gameProgram.account.gamePlayer.fetch(gamePlayerPDA(otto.publicKey, gamePDA));
+      assertEqual(target.health, 100, "Otto should be untouched");
+    });
+
+    it("Fails to resume as a guest", async () => {
+      await assertFails(resume(otto), "NotLobbyOwner");
+    });
+
+    it("Resumes the match and stops counting the pause", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+      await resume(nina);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.gameState, 1, "Game should be active again");
+      assertEqual(game.pausedAt.toNumber(), 0, "Pause time should be cleared");
+      assertTrue(game.totalPausedTime.toNumber() >= 1, "Paused time should be added to the total");
+      assertTrue(game.resumedAt.toNumber() > 0, "Resume time should be recorded");
+    });
+
+    it("Accepts input again after resuming", async () => {
+      await stepForward(otto);
+
+      const gamePlayer = await gameProgram.account.gamePlayer.fetch(gamePlayerPDA(otto.publicKey, gamePDA));
+      assertTrue(gamePlayer.positionZ !== 0 || gamePlayer.positionX !== 5, "Otto should have moved");
+    });
+
+    it("Fails to resume a match that is not paused", async () => {
+      await assertFails(resume(nina), "GameNotPaused");
+    });
+  });
+
+  describe("Match Settlement", () => {
+    let alice: anchor.web3.Keypair;
+    let bob: anchor.web3.Keypair;
+    let gamePDA: anchor.web3.PublicKey;
+
+    const matchRecordPDA = () => settlementRecordPDA(gamePDA);
+
+    before(async () => {
+      alice = await registerPlayer("settle_alice");
+      bob = await registerPlayer("settle_bob");
+      gamePDA = await startMatch(alice, bob);
+
+      // Base layer GamePlayers - no rollup is needed to play out the stats
+      // Alice at the origin facing +X, Bob 5 units down the line of fire
+      for (const [wallet, team, x] of [[alice, 1, 0], [bob, 2, 5]] as [anchor.web3.Keypair, number, number][]) {
+        await gameProgram.methods
+          .initGamePlayer(gamePDA, team, false, x, 0, 0)
+          .accountsPartial({
+            gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+            authority: wallet.publicKey,
+          })
+          .signers([wallet])
+          .rpc();
+      }
+    });
+
+    it("Fails to settle a match that is still running", async () => {
+      await assertFails(settleMatch(gamePDA, [alice, bob]), "GameNotEnded");
+    });
+
+    function shoot(shooter: anchor.web3.Keypair, target: anchor.web3.Keypair, signer = shooter) {
+      return gameProgram.methods
+        .shoot(100)
+        .accountsPartial({
+          shooter: gamePlayerPDA(shooter.publicKey, gamePDA),
+          game: gamePDA,
+          authority: signer.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: gamePlayerPDA(target.publicKey, gamePDA), isSigner: false, isWritable: true },
+        ])
+        .signers([signer])
+        .rpc();
+    }
+
+    function closeGamePlayer(wallet: anchor.web3.Keypair) {
+      return gameProgram.methods
+        .closeGamePlayer()
+        .accountsPartial({
+          gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+          game: gamePDA,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    it("Fails to shoot on behalf of another player", async () => {
+      await assertFails(shoot(alice, bob, bob), "ConstraintHasOne");
+    });
+
+    it("Fails to close a GamePlayer while the match is running", async () => {
+      await assertFails(closeGamePlayer(alice), "MatchNotEnded");
+    });
+
+    it("Records the results reported by the game program", async () => {
+      await shoot(alice, bob);
+
+      const victim = await gameProgram.account.gamePlayer.fetch(gamePlayerPDA(bob.publicKey, gamePDA));
+      assertEqual(victim.isAlive, false, "Bob should be killed by a full damage hit");
+
+      await program.methods
+        .endGame()
+        .accountsPartial({
+          game: gamePDA,
+          authority: alice.publicKey,
+        })
+        .signers([alice])
+        .rpc();
+
+      // Nothing reported yet and the settlement timeout has not passed
+      await assertFails(settleMatch(gamePDA, [alice, bob]), "ResultsPending");
+      // Closing before reporting would drop the result
+      await assertFails(closeGamePlayer(bob), "ResultNotReported");
+
+      await settleGamePlayer(gamePDA, alice);
+      await settleGamePlayer(gamePDA, bob);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.resultsReported, 2, "Both results should be reported");
+      assertEqual(game.teamAScore, 100, "Alice's kill should score a fixed 100 for team A");
+      assertEqual(game.teamAKills, 1, "Alice's kill should count for team A");
+      assertEqual(game.teamBScore, 0, "Team B should not have scored");
+
+      const record = await program.account.matchRecord.fetch(matchRecordPDA());
+      assertEqual(record.players.length, 2, "Match record should hold both stat lines");
+      assertEqual(record.mapId, mapId, "Match record should name the map");
+    });
+
+    it("Stores every player's stat line in the match record", async () => {
+      const game = await program.account.game.fetch(gamePDA);
+      const record = await program.account.matchRecord.fetch(matchRecordPDA());
+      assertEqual(record.game.toString(), gamePDA.toString(), "Match record should point at the game");
+      assertEqual(record.mapVersion, game.mapVersion, "Match record should pin the map revision");
+      assertEqual(record.matchType, game.matchType, "Match record should keep the match type");
+      assertEqual(record.startedAt.toNumber(), game.matchStartTimestamp.toNumber(), "Start time should be copied");
+      assertTrue(record.endedAt.toNumber() >= record.startedAt.toNumber(), "Match should end after it started");
+
+      const lineOf = (wallet: anchor.web3.Keypair) =>
+        record.players.find((line) => line.player.equals(playerPDA(wallet.publicKey)));
+      const aliceLine = lineOf(alice);
+      assertEqual(aliceLine.authority.toString(), alice.publicKey.toString(), "Stat line should name the wallet");
+      assertEqual(aliceLine.team, 1, "Alice played on team A");
+      assertEqual(aliceLine.kills, 1, "Alice scored one kill");
+      assertEqual(aliceLine.deaths, 0, "Alice never died");
+      assertEqual(aliceLine.score, 100, "Alice's score is one kill");
+
+      const bobLine = lineOf(bob);
+      assertEqual(bobLine.team, 2, "Bob played on team B");
+      assertEqual(bobLine.kills, 0, "Bob scored no kills");
+      assertEqual(bobLine.deaths, 1, "Bob died once");
+      assertEqual(bobLine.score, 0, "Bob did not score");
+
+      for (const wallet of [alice, bob]) {
+        const history = await program.account.playerMatchHistory.fetch(pda([MATCH_HISTORY_SEED, wallet.publicKey.toBuffer()]));
+        assertEqual(history.owner.toString(), wallet.publicKey.toString(), "History should belong to the wallet");
+        assertEqual(history.matchCount, 1, "History should count the match");
+        assertTrue(history.matchRecords[0].equals(matchRecordPDA()), "History should list the match record");
+      }
+    });
+
+    it("Settles the match and applies the Elo update", async () => {
+      await settleMatch(gamePDA, [alice, bob]);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.isSettled, true, "Game should be settled");
+      assertEqual(game.winningTeam, 1, "Team A should win on score");
+
+      // Equal 1000 ratings and provisional K = 48: the winner takes 24 from the loser
+      const winner = await program.account.player.fetch(playerPDA(alice.publicKey));
+      const loser = await program.account.player.fetch(playerPDA(bob.publicKey));
+      assertEqual(winner.rating, 1024, "Winner should gain 24 rating");
+      assertEqual(loser.rating, 976, "Loser should lose 24 rating");
+
+      // Match 50 + kill 10 + score 100 + win 100 for Alice, match 50 for Bob
+      assertEqual(winner.xp.toNumber(), 260, "Winner XP should include the win bonus");
+      assertEqual(loser.xp.toNumber(), 50, "Loser should only get participation XP");
+      assertEqual(winner.level, 2, "260 XP should reach level 2");
+
+      for (const player of [winner, loser]) {
+        assertEqual(player.currentGame, null, "Players should be released from the game");
+        assertEqual(player.totalMatchesPlayed, 1, "Match should be counted");
+      }
+
+      const record = await program.account.matchRecord.fetch(matchRecordPDA());
+      assertEqual(record.winningTeam, 1, "Match record should store the winner");
+      assertEqual(record.teamAScore, 100, "Match record should store team A's score");
+      assertEqual(record.teamAKills, 1, "Match record should store team A's kills");
+      assertEqual(record.teamBScore, 0, "Match record should store team B's score");
+      assertEqual(record.teamBKills, 0, "Match record should store team B's kills");
+    });
+
+    it("Fails to settle the same match twice", async () => {
+      await assertFails(settleMatch(gamePDA, [alice, bob]), "AlreadySettled");
+    });
+
+    it("Closes a GamePlayer and refunds its rent", async () => {
+      const gamePlayer = gamePlayerPDA(alice.publicKey, gamePDA);
+      const rent = await provider.connection.getBalance(gamePlayer);
+      const balanceBefore = await provider.connection.getBalance(alice.publicKey);
+
+      // The provider wallet pays the fee
+      await closeGamePlayer(alice);
+
+      const balanceAfter = await provider.connection.getBalance(alice.publicKey);
+      assertEqual(balanceAfter - balanceBefore, rent, "Alice should get the GamePlayer's rent back");
+      assertEqual(await gameProgram.account.gamePlayer.fetchNullable(gamePlayer), null, "GamePlayer should be closed");
+    });
+  });
+
+  describe("Reconnect", () => {
+    let carol: anchor.web3.Keypair;
+    let dave: anchor.web3.Keypair;
+    let gamePDA: anchor.web3.PublicKey;
+
+    function leave(wallet: anchor.web3.Keypair) {
+      return program.methods
+        .leaveGame()
+        .accountsPartial({
+          game: gamePDA,
+          player: playerPDA(wallet.publicKey),
+          lobbyDirectory: null,
+          nextOwner: null,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    function rejoin(wallet: anchor.web3.Keypair) {
+      return program.methods
+        .rejoinGame()
+        .accountsPartial({
+          game: gamePDA,
+          player: playerPDA(wallet.publicKey),
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    before(async () => {
+      carol = await registerPlayer("rejoin_carol");
+      dave = await registerPlayer("rejoin_dave");
+      gamePDA = await startMatch(carol, dave);
+
+      await gameProgram.methods
+        .initGamePlayer(gamePDA, 1, false, 0, 0, 0)
+        .accountsPartial({
+          gamePlayer: gamePlayerPDA(carol.publicKey, gamePDA),
+          authority: carol.publicKey,
+        })
+        .signers([carol])
+        .rpc();
+    });
+
+    it("Holds the slot of a player who drops out mid-match", async () => {
+      await leave(dave);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.gameState, 1, "Match should keep running");
+      assertEqual(game.currentPlayersTeamB, 0, "Dave should be off team B");
+      assertEqual(game.readyPlayers, 1, "Dave should no longer count as ready");
+      assertEqual(game.reservedSlots.length, 1, "Dave's slot should be held");
+      assertEqual(game.reservedSlots[0].player.toString(), playerPDA(dave.publicKey).toString(), "Slot should belong to Dave");
+      assertEqual(game.reservedSlots[0].team, 2, "Slot should be on Dave's team");
+
+      const player = await program.account.player.fetch(playerPDA(dave.publicKey));
+      assertEqual(player.currentGame, null, "Dave should be out of the game");
+      assertEqual(player.isReady, false, "Dave should not be ready");
+    });
+
+    it("Rejoins on the same team within the grace period", async () => {
+      await rejoin(dave);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.currentPlayersTeamB, 1, "Dave should be back on team B");
+      assertTrue(
+        game.teamBPlayers.some((key) => key.equals(playerPDA(dave.publicKey))),
+        "Team B roster should list Dave"
+      );
+      assertEqual(game.readyPlayers, 2, "Ready count should match the ready players again");
+      assertEqual(game.reservedSlots.length, 0, "Reservation should be used up");
+
+      const player = await program.account.player.fetch(playerPDA(dave.publicKey));
+      assertEqual(player.currentGame.toString(), gamePDA.toString(), "Dave should be back in the game");
+      assertEqual(player.team, 2, "Dave should be on team B");
+      assertEqual(player.isReady, true, "Dave should be ready");
+    });
+
+    it("Fails to rejoin while already in the game", async () => {
+      await assertFails(rejoin(dave), "PlayerAlreadyInGame");
+    });
+
+    it("Fails to rejoin without a reserved slot", async () => {
+      const stranger = await registerPlayer("rejoin_erin");
+      await assertFails(rejoin(stranger), "NoReservedSlot");
+    });
+
+    it("Fails to rejoin once the match has ended", async () => {
+      await leave(dave);
+      await program.methods
+        .endGame()
+        .accountsPartial({
+          game: gamePDA,
+          authority: carol.publicKey,
+        })
+        .signers([carol])
+        .rpc();
+
+      await assertFails(rejoin(dave), "MatchNotInProgress");
+    });
+
+    it("Fails to settle without the players who left", async () => {
+      await settleGamePlayer(gamePDA, carol);
+
+      await assertFails(settleMatch(gamePDA, [carol]), "RosterMismatch");
+    });
+
+    it("Rates a player who left before settlement as a loss", async () => {
+      await settleMatch(gamePDA, [carol, dave]);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.winningTeam, 0, "Nobody scored, so the match is a draw");
+
+      // Equal 1000 ratings and provisional K = 48: a draw changes nothing, a loss costs 24
+      const stayer = await program.account.player.fetch(playerPDA(carol.publicKey));
+      const leaver = await program.account.player.fetch(playerPDA(dave.publicKey));
+      assertEqual(stayer.rating, 1000, "Carol's draw should not move her rating");
+      assertEqual(leaver.rating, 976, "Dave should take the loss for leaving");
+      assertEqual(leaver.totalMatchesPlayed, 1, "The abandoned match should be counted");
+    });
+  });
+
+  describe("Seasons", () => {
+    let wendy: anchor.web3.Keypair;
+    let xavier: anchor.web3.Keypair;
+    let yara: anchor.web3.Keypair;
+    let zed: anchor.web3.Keypair;
+    let seasonId: number;
+
+    const seasonPDA = (id: number) => pda([SEASON_SEED, u32Bytes(id)]);
+
+    // The season opened is always the one after config.current_season
+    function openSeason(id: number, signer?: anchor.web3.Keypair) {
+      return program.methods
+        .openSeason()
+        .accountsPartial({
+          config: configPDA,
+          season: seasonPDA(id),
+          admin: signer ? signer.publicKey : admin,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    }
+
+    function closeSeason(signer?: anchor.web3.Keypair) {
+      return program.methods
+        .closeSeason()
+        .accountsPartial({
+          config: configPDA,
+          season: seasonPDA(seasonId),
+          admin: signer ? signer.publicKey : admin,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    }
+
+    // `winner` owns the lobby, kills `loser` once and both report their results
+    async function playMatch(winner: anchor.web3.Keypair, loser: anchor.web3.Keypair) {
+      const gamePDA = await startMatch(winner, loser);
+      for (const [wallet, team, x] of [[winner, 1, 0], [loser, 2, 5]] as [anchor.web3.Keypair, number, number][]) {
+        await gameProgram.methods
+          .initGamePlayer(gamePDA, team, false, x, 0, 0)
+          .accountsPartial({
+            gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+            authority: wallet.publicKey,
+          })
+          .signers([wallet])
+          .rpc();
+      }
+
+      await gameProgram.methods
+        .shoot(100)
+        .accountsPartial({
+          shooter: gamePlayerPDA(winner.publicKey, gamePDA),
+          game: gamePDA,
+          authority: winner.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: gamePlayerPDA(loser.publicKey, gamePDA), isSigner: false, isWritable: true },
+        ])
+        .signers([winner])
+        .rpc();
+      await program.methods
+        .endGame()
+        .accountsPartial({
+          game: gamePDA,
+          authority: winner.publicKey,
+        })
+        .signers([winner])
+        .rpc();
+
+      await settleGamePlayer(gamePDA, winner);
+      await settleGamePlayer(gamePDA, loser);
+      return gamePDA;
+    }
+
+    function assertRanked(board: any[], message: string) {
+      for (let i = 1; i < board.length; i++) {
+        assertTrue(board[i - 1].value.gte(board[i].value), message);
+      }
+    }
+
+    before(async () => {
+      wendy = await registerPlayer("season_wendy");
+      xavier = await registerPlayer("season_xavier");
+      yara = await registerPlayer("season_yara");
+      zed = await registerPlayer("season_zed");
+
+      const config = await program.account.matchmakingConfig.fetch(configPDA);
+      seasonId = config.currentSeason + 1;
+    });
+
+    it("Fails to open a season as a non-admin", async () => {
+      await assertFails(openSeason(seasonId, wendy), "Unauthorized");
+    });
+
+    it("Opens the next season", async () => {
+      await openSeason(seasonId);
+
+      const config = await program.account.matchmakingConfig.fetch(configPDA);
+      assertEqual(config.currentSeason, seasonId, "Config should point at the new season");
+      assertEqual(config.seasonActive, true, "Season should be running");
+
+      const season = await program.account.season.fetch(seasonPDA(seasonId));
+      assertEqual(season.seasonId, seasonId, "Season should know its id");
+      assertEqual(season.isActive, true, "Season should be active");
+      assertEqual(season.endedAt, null, "Season should not have ended");
+      assertEqual(season.topRating.length, 0, "Leaderboards should start empty");
+    });
+
+    it("Fails to open a season while one is running", async () => {
+      await assertFails(openSeason(seasonId + 1), "SeasonAlreadyActive");
+    });
+
+    it("Fails to settle without the running season", async () => {
+      const gamePDA = await playMatch(wendy, xavier);
+
+      await assertFails(settleMatch(gamePDA, [wendy, xavier]), "SeasonRequired");
+      await settleMatch(gamePDA, [wendy, xavier], seasonPDA(seasonId));
+    });
+
+    it("Ranks settled players on the season leaderboards", async () => {
+      const gamePDA = await playMatch(zed, yara);
+      await settleMatch(gamePDA, [zed, yara], seasonPDA(seasonId));
+
+      const season = await program.account.season.fetch(seasonPDA(seasonId));
+      for (const board of [season.topRating, season.topKills, season.topWins]) {
+        assertEqual(board.length, 4, "Every settled player should be ranked");
+        assertRanked(board, "Leaderboard should be sorted highest first");
+      }
+
+      // Equal ratings keep the earlier entry in front
+      const ranking = season.topRating.map((entry) => entry.player.toString());
+      assertEqual(ranking[0], playerPDA(wendy.publicKey).toString(), "Wendy settled first at 1024");
+      assertEqual(ranking[1], playerPDA(zed.publicKey).toString(), "Zed ties Wendy at 1024");
+      assertEqual(season.topRating[0].value.toNumber(), 1024, "Winners should rank on their new rating");
+      assertEqual(season.topRating[3].value.toNumber(), 976, "Losers should rank last");
+      assertEqual(season.topWins[0].value.toNumber(), 1, "Winners should have one season win");
+      assertEqual(season.topKills[3].value.toNumber(), 0, "Losers should have no season kills");
+
+      const player = await program.account.player.fetch(playerPDA(wendy.publicKey));
+      assertEqual(player.seasonId, seasonId, "Wendy's season stats should be for this season");
+      assertEqual(player.seasonKills, 1, "Wendy should have one season kill");
+      assertEqual(player.seasonWins, 1, "Wendy should have one season win");
+    });
+
+    it("Fails to close a season as a non-admin", async () => {
+      await assertFails(closeSeason(wendy), "Unauthorized");
+    });
+
+    it("Closes the season and keeps its standings", async () => {
+      await closeSeason();
+
+      const config = await program.account.matchmakingConfig.fetch(configPDA);
+      assertEqual(config.seasonActive, false, "No season should be running");
+
+      const season = await program.account.season.fetch(seasonPDA(seasonId));
+      assertEqual(season.isActive, false, "Season should be closed");
+      assertTrue(season.endedAt !== null, "Season should record when it ended");
+      assertEqual(season.topRating.length, 4, "Standings should be archived");
+    });
+
+    it("Fails to close a season that is not running", async () => {
+      await assertFails(closeSeason(), "SeasonNotActive");
+    });
+  });
+
+  describe("Idle Kick", () => {
+    const providerEphemeralRollup = new anchor.AnchorProvider(
+      new anchor.web3.Connection(
+        process.env.EPHEMERAL_PROVIDER_ENDPOINT ||
+          "https://devnet-as.magicblock.app/",
+        {
+          wsEndpoint:
+            process.env.EPHEMERAL_WS_ENDPOINT || "wss://devnet-as.magicblock.app/",
+        },
+      ),
+      anchor.Wallet.local(),
+    );
+    const gameProgramEphemeral = new Program<GameProgram>(gameProgram.idl, providerEphemeralRollup);
+
+    // Local validator identity, only needed when the rollup runs on localnet
+    const validatorAccounts =
+      providerEphemeralRollup.connection.rpcEndpoint.includes("localhost") ||
+      providerEphemeralRollup.connection.rpcEndpoint.includes("127.0.0.1")
+        ? [
+            {
+              pubkey: new anchor.web3.PublicKey("mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"),
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : [];
+
+    const IDLE_TIMEOUT = 10;
+
+    let frank: anchor.web3.Keypair;
+    let grace: anchor.web3.Keypair;
+    let gamePDA: anchor.web3.PublicKey;
+
+    function setIdleTimeout(wallet: anchor.web3.Keypair, idleTimeout: number) {
+      return program.methods
+        .setIdleTimeout(new anchor.BN(idleTimeout))
+        .accountsPartial({
+          game: gamePDA,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet])
+        .rpc();
+    }
+
+    function kickIdle(wallet: anchor.web3.Keypair) {
+      return gameProgramEphemeral.methods
+        .kickIdlePlayer()
+        .accountsPartial({
+          payer: providerEphemeralRollup.wallet.publicKey,
+          gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+          game: gamePDA,
+        })
+        .rpc();
+    }
+
+    function removeIdle(wallet: anchor.web3.Keypair) {
+      return gameProgram.methods
+        .removeIdlePlayer()
+        .accountsPartial({
+          gamePlayer: gamePlayerPDA(wallet.publicKey, gamePDA),
+          game: gamePDA,
+          player: playerPDA(wallet.publicKey),
+          settlementAuthority: settlementAuthorityPDA,
+          matchmakingProgram: program.programId,
+        })
+        .rpc();
+    }
+
+    // Base layer sees the GamePlayer again once the rollup hands it back
+    async function waitForUndelegation(account: anchor.web3.PublicKey) {
+      for (let attempt = 0; attempt < 30; attempt++) {
+        const info = await provider.connection.getAccountInfo(account);
+        if (info && info.owner.equals(gameProgram.programId)) {
+          return;
+        }
+        await new Promise((resolve) => setTimeout(resolve, 1000));
+      }
+      assertFail(`${account.toString()} was not undelegated`);
+    }
+
+    before(async () => {
+      frank = await registerPlayer("idle_frank");
+      grace = await registerPlayer("idle_grace");
+
+      gamePDA = await createLobby(frank);
+      await joinLobby(gamePDA, grace);
+    });
+
+    it("Fails to set the idle timeout as a guest", async () => {
+      await assertFails(setIdleTimeout(grace, IDLE_TIMEOUT), "NotLobbyOwner");
+    });
+
+    it("Fails to set an idle timeout below the minimum", async () => {
+      await assertFails(setIdleTimeout(frank, IDLE_TIMEOUT - 1), "IdleTimeoutTooShort");
+    });
+
+    it("Sets the idle timeout while the lobby is waiting", async () => {
+      await setIdleTimeout(frank, IDLE_TIMEOUT);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.idleTimeout.toNumber(), IDLE_TIMEOUT, "Idle timeout should be updated");
+    });
+
+    it("Fails to change the idle timeout once the match has started", async () => {
+      await setReady(gamePDA, frank);
+      await setReady(gamePDA, grace);
+      await startLobby(gamePDA, frank);
+
+      await assertFails(setIdleTimeout(frank, IDLE_TIMEOUT), "GameAlreadyStarted");
+    });
+
+    it("Fails to remove a player who was never flagged idle", async () => {
+      await gameProgram.methods
+        .initGamePlayer(gamePDA, 2, false, 0, 0, 0)
+        .accountsPartial({
+          gamePlayer: gamePlayerPDA(grace.publicKey, gamePDA),
+          authority: grace.publicKey,
+        })
+        .signers([grace])
+        .rpc();
+
+      await assertFails(removeIdle(grace), "NotFlaggedIdle");
+    });
+
+    it("Fails to kick a player within the idle timeout", async () => {
+      await gameProgram.methods
+        .delegateGamePlayer(gamePDA)
+        .accountsPartial({
+          gamePlayer: gamePlayerPDA(grace.publicKey, gamePDA),
+          authority: grace.publicKey,
+          signer: provider.wallet.publicKey,
+        })
+        .remainingAccounts(validatorAccounts)
+        .signers([grace])
+        .rpc();
+
+      await assertFails(kickIdle(grace), "PlayerNotIdle");
+    });
+
+    it("Kicks a player once the idle timeout has passed", async () => {
+      await new Promise((resolve) => setTimeout(resolve, (IDLE_TIMEOUT + 2) * 1000));
+
+      const txHash = await kickIdle(grace);
+      await GetCommitmentSignature(txHash, providerEphemeralRollup.connection);
+      await waitForUndelegation(gamePlayerPDA(grace.publicKey, gamePDA));
+
+      const gamePlayer = await gameProgram.account.gamePlayer.fetch(gamePlayerPDA(grace.publicKey, gamePDA));
+      assertEqual(gamePlayer.isIdle, true, "GamePlayer should be flagged idle");
+      assertEqual(gamePlayer.isAlive, false, "Kicked player should be dead");
+      assertEqual(gamePlayer.health, 0, "Kicked player should have no health");
+    });
+
+    it("Removes the kicked player from their team", async () => {
+      await removeIdle(grace);
+
+      const game = await program.account.game.fetch(gamePDA);
+      assertEqual(game.gameState, 1, "Match should keep running");
+      assertEqual(game.currentPlayersTeamB, 0, "Grace should be off team B");
+      assertTrue(
+        !game.teamBPlayers.some((key) => key.equals(playerPDA(grace.publicKey))),
+        "Team B roster should not list Grace"
+      );
+      assertEqual(game.readyPlayers, 1, "Grace should no longer count as ready");
+      assertEqual(game.reservedSlots.length, 1, "Grace's slot should be held for a reconnect");
+      assertEqual(game.reservedSlots[0].team, 2, "Slot should be on Grace's team");
+
+      const player = await program.account.player.fetch(playerPDA(grace.publicKey));
+      assertEqual(player.currentGame, null, "Grace should be out of the game");
+      assertEqual(player.team, 0, "Grace should have no team");
+      assertEqual(player.isReady, false, "Grace should not be ready");
+    });
+
+    it("Fails to remove a player who is no longer in the game", async () => {
+      await assertFails(removeIdle(grace), "PlayerNotInGame");
+    });
+
+    it("Reconnects a kicked player at the spawn point", async () => {
+      await program.methods
+        .rejoinGame()
+        .accountsPartial({
+          game: gamePDA,
+          player: playerPDA(grace.publicKey),
+          authority: grace.publicKey,
+        })
+        .signers([grace])
+        .rpc();
+
+      await gameProgram.methods
+        .initGamePlayer(gamePDA, 2, false, 5, 0, 5)
+        .accountsPartial({
+          gamePlayer: gamePlayerPDA(grace.publicKey, gamePDA),
+          authority: grace.publicKey,
+        })
+        .signers([grace])
+        .rpc();
+
+      const gamePlayer = await gameProgram.account.gamePlayer.fetch(gamePlayerPDA(grace.publicKey, gamePDA));
+      assertEqual(gamePlayer.isIdle, false, "Idle flag should be cleared");
+      assertEqual(gamePlayer.isAlive, true, "Grace should be back alive");
+      assertEqual(gamePlayer.health, 100, "Grace should be back at full health");
+      assertEqual(gamePlayer.positionX, 5, "Grace should be at the spawn point");
+      assertEqual(gamePlayer.positionZ, 5, "Grace should be at the spawn point");
+      assertTrue(
+        gamePlayer.lastUpdate.toNumber() >= Math.floor(Date.now() / 1000) - IDLE_TIMEOUT,
+        "Idle timer should restart from the reconnect"
+      );
+
+      await assertFails(removeIdle(grace), "NotFlaggedIdle");
+    });
   });
 });